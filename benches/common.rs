@@ -1,3 +1,4 @@
+use advent_of_code_2024::input::normalize;
 use criterion::{black_box, Criterion};
 use reqwest::blocking::Client;
 use std::env;
@@ -24,6 +25,11 @@ where
 {
     let input = fetch_input(day);
 
+    // Cost the dispatch layer adds on top of the solvers
+    c.bench_function(&format!("day{}_normalize", day), |b| {
+        b.iter(|| normalize(black_box(&input)))
+    });
+
     c.bench_function(&format!("day{}_part1", day), |b| {
         b.iter(|| part1(black_box(&input)))
     });
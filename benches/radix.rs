@@ -0,0 +1,70 @@
+use advent_of_code_2024::radix;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::Rng;
+
+const SIZES: [usize; 3] = [1_000, 100_000, 1_000_000];
+
+fn random<T>(len: usize, f: impl Fn(&mut rand::rngs::ThreadRng) -> T) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| f(&mut rng)).collect()
+}
+
+macro_rules! bench_sorts {
+    ($c:expr, $name:literal, $gen:expr, $std:expr, $radix:expr $(, $par:expr)?) => {
+        let mut group = $c.benchmark_group($name);
+        for len in SIZES {
+            let data = random(len, $gen);
+            group.bench_with_input(BenchmarkId::new("sort_unstable", len), &data, |b, data| {
+                b.iter_batched_ref(|| data.clone(), $std, BatchSize::LargeInput)
+            });
+            group.bench_with_input(BenchmarkId::new("radix", len), &data, |b, data| {
+                b.iter_batched_ref(|| data.clone(), $radix, BatchSize::LargeInput)
+            });
+            $(
+                #[cfg(feature = "parallel")]
+                group.bench_with_input(BenchmarkId::new("radix_parallel", len), &data, |b, data| {
+                    b.iter_batched_ref(|| data.clone(), $par, BatchSize::LargeInput)
+                });
+            )?
+        }
+        group.finish();
+    };
+}
+
+fn register_benchmarks(c: &mut Criterion) {
+    bench_sorts!(
+        c,
+        "radix_u32",
+        |rng| rng.gen::<u32>(),
+        |v: &mut Vec<u32>| v.sort_unstable(),
+        |v: &mut Vec<u32>| radix::sort(v),
+        |v: &mut Vec<u32>| radix::par_sort(v)
+    );
+    bench_sorts!(
+        c,
+        "radix_u32_5_digit",
+        |rng| rng.gen_range(10_000u32..=99_999),
+        |v: &mut Vec<u32>| v.sort_unstable(),
+        |v: &mut Vec<u32>| radix::sort(v),
+        |v: &mut Vec<u32>| radix::par_sort(v)
+    );
+    bench_sorts!(
+        c,
+        "radix_u64",
+        |rng| rng.gen::<u64>(),
+        |v: &mut Vec<u64>| v.sort_unstable(),
+        |v: &mut Vec<u64>| radix::sort(v),
+        |v: &mut Vec<u64>| radix::par_sort(v)
+    );
+    bench_sorts!(
+        c,
+        "radix_pairs",
+        |rng| (rng.gen::<u32>(), rng.gen::<u32>()),
+        |v: &mut Vec<(u32, u32)>| v.sort_by_key(|&(k, _)| k),
+        |v: &mut Vec<(u32, u32)>| radix::sort_pairs(v),
+        |v: &mut Vec<(u32, u32)>| radix::par_sort_pairs(v)
+    );
+}
+
+criterion_group!(benches, register_benchmarks);
+criterion_main!(benches);
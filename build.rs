@@ -256,5 +256,8 @@ fn main() {
         }
     }
 
-    build_day11_lut()
+    // Lean builds don't embed the day 11 LUTs
+    if env::var_os("CARGO_FEATURE_LEAN").is_none() {
+        build_day11_lut()
+    }
 }
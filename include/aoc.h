@@ -0,0 +1,29 @@
+/* Generated from src/ffi.rs, do not edit. */
+#ifndef AOC_H
+#define AOC_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define AOC_ERR_NULL_POINTER (-1)
+#define AOC_ERR_UNKNOWN_PUZZLE (-2)
+#define AOC_ERR_INVALID_INPUT (-3)
+#define AOC_ERR_BUFFER_TOO_SMALL (-4)
+#define AOC_ERR_PANIC (-5)
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Solves `part` of `day` for `input` and writes the answer to `out_buf` as a
+ * NUL-terminated string. Returns the answer length without the NUL, or a negative
+ * AOC_ERR_* code. Not thread-safe. */
+int32_t aoc_solve(uint32_t year, uint32_t day, uint32_t part,
+                  const uint8_t *input_ptr, size_t input_len,
+                  uint8_t *out_buf, size_t out_cap);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AOC_H */
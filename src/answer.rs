@@ -0,0 +1,143 @@
+//! Fixed-capacity string answers stored inline, so solvers can return them by value
+//! instead of handing out references into a shared static buffer.
+
+use core::fmt;
+use core::ops::Deref;
+use core::str;
+
+#[derive(Clone, Copy)]
+pub struct InlineStr<const N: usize> {
+    len: usize,
+    bytes: [u8; N],
+}
+
+impl<const N: usize> InlineStr<N> {
+    pub const CAPACITY: usize = N;
+
+    pub const fn new() -> Self {
+        Self {
+            len: 0,
+            bytes: [0; N],
+        }
+    }
+
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        // Only ASCII is ever pushed
+        unsafe { str::from_utf8_unchecked(self.bytes.get_unchecked(..self.len)) }
+    }
+
+    /// Appends an ASCII byte, panicking if it isn't ASCII or the string is full.
+    #[inline(always)]
+    pub fn push(&mut self, byte: u8) {
+        assert!(byte.is_ascii() && self.len < N);
+        unsafe { self.push_unchecked(byte) }
+    }
+
+    /// Appends a byte without checks.
+    ///
+    /// # Safety
+    /// `byte` must be ASCII and the string must not be full.
+    #[inline(always)]
+    pub unsafe fn push_unchecked(&mut self, byte: u8) {
+        *self.bytes.get_unchecked_mut(self.len) = byte;
+        self.len += 1;
+    }
+
+    /// Shortens the string to `len` bytes; no effect if it is already shorter.
+    #[inline(always)]
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+}
+
+impl<const N: usize> Default for InlineStr<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for InlineStr<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for InlineStr<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Display for InlineStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Debug for InlineStr<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<InlineStr<M>> for InlineStr<N> {
+    fn eq(&self, other: &InlineStr<M>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for InlineStr<N> {}
+
+impl<const N: usize> PartialEq<str> for InlineStr<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for InlineStr<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_and_truncate() {
+        let mut s = InlineStr::<8>::new();
+        assert!(s.is_empty());
+        for &b in b"1,2," {
+            s.push(b);
+        }
+        s.truncate(s.len() - 1);
+        assert_eq!(s, "1,2");
+        assert_eq!(s.to_string(), "1,2");
+        assert_eq!(format!("{:?}", s), "\"1,2\"");
+        s.truncate(10);
+        assert_eq!(s.len(), 3);
+    }
+
+    #[test]
+    fn test_answers_are_independent() {
+        let mut a = InlineStr::<4>::new();
+        a.push(b'a');
+        let mut b = a;
+        b.push(b'b');
+        assert_eq!(a, "a");
+        assert_eq!(b, "ab");
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_push_past_capacity() {
+        let mut s = InlineStr::<1>::new();
+        s.push(b'a');
+        s.push(b'b');
+    }
+}
@@ -0,0 +1,445 @@
+//! Client for adventofcode.com, enabled with the `client` cargo feature.
+//!
+//! Fetches puzzle inputs and pages and submits answers. Requests are spaced at least
+//! `min_interval` apart, transient failures (connection errors, 5xx) are retried with
+//! exponential backoff, and inputs are cached on disk in the same `{year}/day{day}.txt`
+//! layout as the `input` directory, so `with_cache_dir("input")` shares files with the tests.
+
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+const USER_AGENT: &str = "github.com/KasparasMasiukas/advent-of-code-2024 by kmasiukas@gmail.com";
+const DEFAULT_MIN_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum Error {
+    Http(reqwest::Error),
+    Io(io::Error),
+    /// The session cookie is missing or expired.
+    Unauthorized,
+    /// The puzzle hasn't unlocked yet.
+    NotUnlocked,
+    Status(u16),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "HTTP error: {}", e),
+            Error::Io(e) => write!(f, "cache error: {}", e),
+            Error::Unauthorized => write!(f, "session cookie rejected"),
+            Error::NotUnlocked => write!(f, "puzzle not unlocked yet"),
+            Error::Status(status) => write!(f, "unexpected HTTP status {}", status),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// What the server said about a submitted answer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    TooHigh,
+    TooLow,
+    /// Wrong, without a hint.
+    Incorrect,
+    /// Submitted too recently; retry after this long.
+    Wait(Duration),
+    /// The part is already solved or not unlocked yet.
+    WrongLevel,
+    /// Anything else, as the text of the response's `<article>`.
+    Unknown(String),
+}
+
+pub struct AocClient {
+    http: Client,
+    session: String,
+    base_url: String,
+    cache_dir: Option<PathBuf>,
+    min_interval: Duration,
+    retries: u32,
+    backoff: Duration,
+    last_request: Option<Instant>,
+}
+
+impl AocClient {
+    pub fn new(session: &str) -> Result<Self> {
+        let http = Client::builder().user_agent(USER_AGENT).build()?;
+        Ok(Self {
+            http,
+            session: session.trim().to_string(),
+            base_url: BASE_URL.to_string(),
+            cache_dir: None,
+            min_interval: DEFAULT_MIN_INTERVAL,
+            retries: DEFAULT_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            last_request: None,
+        })
+    }
+
+    /// Reads the session cookie from the `AOC_SESSION` environment variable.
+    pub fn from_env() -> Result<Self> {
+        Self::new(&std::env::var("AOC_SESSION").map_err(|_| Error::Unauthorized)?)
+    }
+
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_cache_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    pub fn with_min_interval(mut self, min_interval: Duration) -> Self {
+        self.min_interval = min_interval;
+        self
+    }
+
+    /// Retries transient failures `retries` times, waiting `backoff`, `2 * backoff`, ...
+    pub fn with_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// Returns the puzzle input, from the cache if present.
+    pub fn input(&mut self, year: u32, day: u32) -> Result<String> {
+        let cache_path = self
+            .cache_dir
+            .as_ref()
+            .map(|dir| dir.join(year.to_string()).join(format!("day{}.txt", day)));
+        if let Some(path) = &cache_path {
+            match fs::read_to_string(path) {
+                Ok(input) if !input.is_empty() => return Ok(input),
+                _ => {}
+            }
+        }
+
+        let url = format!("{}/{}/day/{}/input", self.base_url, year, day);
+        let input = self.send(|http| http.get(&url))?;
+        if let Some(path) = &cache_path {
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, &input)?;
+        }
+        Ok(input)
+    }
+
+    /// Returns the puzzle page HTML. Not cached, as part 2 appears once part 1 is solved.
+    pub fn puzzle(&mut self, year: u32, day: u32) -> Result<String> {
+        let url = format!("{}/{}/day/{}", self.base_url, year, day);
+        self.send(|http| http.get(&url))
+    }
+
+    pub fn submit(&mut self, year: u32, day: u32, part: u32, answer: &str) -> Result<Outcome> {
+        let url = format!("{}/{}/day/{}/answer", self.base_url, year, day);
+        let level = part.to_string();
+        let html = self.send(|http| {
+            http.post(&url)
+                .form(&[("level", level.as_str()), ("answer", answer)])
+        })?;
+        Ok(parse_outcome(&html))
+    }
+
+    fn send<F: Fn(&Client) -> RequestBuilder>(&mut self, request: F) -> Result<String> {
+        let mut attempt = 0;
+        loop {
+            if let Some(last) = self.last_request {
+                let next = last + self.min_interval;
+                let now = Instant::now();
+                if next > now {
+                    thread::sleep(next - now);
+                }
+            }
+            self.last_request = Some(Instant::now());
+
+            let response = request(&self.http)
+                .header("Cookie", format!("session={}", self.session))
+                .send();
+            let transient = match response {
+                Ok(response) => match response.status() {
+                    StatusCode::OK => return Ok(response.text()?),
+                    StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED => {
+                        return Err(Error::Unauthorized)
+                    }
+                    StatusCode::NOT_FOUND => return Err(Error::NotUnlocked),
+                    status if status.is_server_error() => Error::Status(status.as_u16()),
+                    status => return Err(Error::Status(status.as_u16())),
+                },
+                Err(e) if e.is_connect() || e.is_timeout() => Error::Http(e),
+                Err(e) => return Err(Error::Http(e)),
+            };
+
+            if attempt == self.retries {
+                return Err(transient);
+            }
+            thread::sleep(self.backoff * 2u32.pow(attempt));
+            attempt += 1;
+        }
+    }
+}
+
+/// Classifies an answer submission response by the text of its `<article>`.
+pub fn parse_outcome(html: &str) -> Outcome {
+    let text = article_text(html);
+    if text.contains("That's the right answer") {
+        Outcome::Correct
+    } else if text.contains("That's not the right answer") {
+        if text.contains("too high") {
+            Outcome::TooHigh
+        } else if text.contains("too low") {
+            Outcome::TooLow
+        } else {
+            Outcome::Incorrect
+        }
+    } else if text.contains("You gave an answer too recently") {
+        Outcome::Wait(parse_wait(&text).unwrap_or(Duration::from_secs(60)))
+    } else if text.contains("You don't seem to be solving the right level") {
+        Outcome::WrongLevel
+    } else {
+        Outcome::Unknown(text)
+    }
+}
+
+/// Text inside the first `<article>` (or the whole page), with tags dropped and whitespace collapsed.
+fn article_text(html: &str) -> String {
+    let article = html
+        .find("<article")
+        .and_then(|start| {
+            let end = html[start..].find("</article>")?;
+            Some(&html[start..start + end])
+        })
+        .unwrap_or(html);
+
+    let mut text = String::with_capacity(article.len());
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Parses "You have 1m 23s left to wait".
+fn parse_wait(text: &str) -> Option<Duration> {
+    let start = text.find("You have ")? + "You have ".len();
+    let end = start + text[start..].find(" left to wait")?;
+    let mut secs = 0;
+    for part in text[start..end].split_whitespace() {
+        let (num, unit) = part.split_at(part.len() - 1);
+        let num: u64 = num.parse().ok()?;
+        secs += match unit {
+            "h" => num * 3600,
+            "m" => num * 60,
+            "s" => num,
+            _ => return None,
+        };
+    }
+    Some(Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    // Recorded response bodies, trimmed to the parts that matter
+    const CORRECT: &str = "<main>\n<article><p>That's the right answer!  You are <span class=\"day-success\">one gold star</span> closer to finding the Chief Historian. <a href=\"/2024/day/1#part2\">[Continue to Part Two]</a></p></article>\n</main>";
+    const TOO_HIGH: &str = "<main>\n<article><p>That's not the right answer; your answer is too high.  If you're stuck, make sure you're using the full input data; there are also some general tips on the <a href=\"/2024/about\">about page</a>, or you can ask for hints on the <a href=\"https://www.reddit.com/r/adventofcode/\" target=\"_blank\">subreddit</a>.  Please wait one minute before trying again. <a href=\"/2024/day/1\">[Return to Day 1]</a></p></article>\n</main>";
+    const TOO_LOW: &str = "<main>\n<article><p>That's not the right answer; your answer is too low.  Please wait one minute before trying again. <a href=\"/2024/day/1\">[Return to Day 1]</a></p></article>\n</main>";
+    const INCORRECT: &str = "<main>\n<article><p>That's not the right answer.  If you're stuck, make sure you're using the full input data.  Please wait one minute before trying again. <a href=\"/2024/day/1\">[Return to Day 1]</a></p></article>\n</main>";
+    const WAIT: &str = "<main>\n<article><p>You gave an answer too recently; you have to wait after submitting an answer before trying again.  You have 1m 23s left to wait. <a href=\"/2024/day/1\">[Return to Day 1]</a></p></article>\n</main>";
+    const WRONG_LEVEL: &str = "<main>\n<article><p>You don't seem to be solving the right level.  Did you already complete it? <a href=\"/2024/day/1\">[Return to Day 1]</a></p></article>\n</main>";
+    const NOT_UNLOCKED: &str = "Please don't repeatedly request this endpoint before it unlocks! The calendar countdown is synchronized with the server time; the link will be enabled on the calendar the instant this puzzle becomes available.\n";
+    const LOGGED_OUT: &str =
+        "Puzzle inputs differ by user.  Please log in to get your puzzle input.\n";
+
+    struct Request {
+        method: String,
+        path: String,
+        headers: Vec<String>,
+        body: String,
+    }
+
+    /// Serves `responses` in order, one per connection, and records the requests.
+    fn mock_server(responses: Vec<(u16, &'static str)>) -> (String, Arc<Mutex<Vec<Request>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let method = parts.next().unwrap().to_string();
+                let path = parts.next().unwrap().to_string();
+
+                let mut headers = Vec::new();
+                let mut content_length = 0;
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    let header = header.trim_end().to_string();
+                    if header.is_empty() {
+                        break;
+                    }
+                    if let Some(len) = header.to_lowercase().strip_prefix("content-length: ") {
+                        content_length = len.parse().unwrap();
+                    }
+                    headers.push(header);
+                }
+                let mut body_bytes = vec![0; content_length];
+                reader.read_exact(&mut body_bytes).unwrap();
+                recorded.lock().unwrap().push(Request {
+                    method,
+                    path,
+                    headers,
+                    body: String::from_utf8(body_bytes).unwrap(),
+                });
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    fn client(url: &str) -> AocClient {
+        AocClient::new("abc123\n")
+            .unwrap()
+            .with_base_url(url)
+            .with_min_interval(Duration::ZERO)
+            .with_retries(2, Duration::ZERO)
+    }
+
+    #[test]
+    fn test_parse_outcome() {
+        assert_eq!(parse_outcome(CORRECT), Outcome::Correct);
+        assert_eq!(parse_outcome(TOO_HIGH), Outcome::TooHigh);
+        assert_eq!(parse_outcome(TOO_LOW), Outcome::TooLow);
+        assert_eq!(parse_outcome(INCORRECT), Outcome::Incorrect);
+        assert_eq!(parse_outcome(WAIT), Outcome::Wait(Duration::from_secs(83)));
+        assert_eq!(parse_outcome(WRONG_LEVEL), Outcome::WrongLevel);
+        assert_eq!(
+            parse_outcome("<article><p>Something <em>new</em>.</p></article>"),
+            Outcome::Unknown("Something new.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_input_is_fetched_once_and_cached() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("aoc_client_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&cache_dir);
+        let (url, requests) = mock_server(vec![(200, "1 2\n3 4\n")]);
+        let mut client = client(&url).with_cache_dir(&cache_dir);
+
+        assert_eq!(client.input(2024, 1).unwrap(), "1 2\n3 4\n");
+        assert_eq!(client.input(2024, 1).unwrap(), "1 2\n3 4\n");
+        assert_eq!(
+            fs::read_to_string(cache_dir.join("2024/day1.txt")).unwrap(),
+            "1 2\n3 4\n"
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/2024/day/1/input");
+        let headers = requests[0].headers.join("\n").to_lowercase();
+        assert!(headers.contains("cookie: session=abc123"));
+        assert!(headers.contains(&format!("user-agent: {}", USER_AGENT.to_lowercase())));
+        fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[test]
+    fn test_submit() {
+        let (url, requests) = mock_server(vec![(200, TOO_LOW), (200, WAIT), (200, CORRECT)]);
+        let mut client = client(&url);
+
+        assert_eq!(client.submit(2024, 1, 2, "31").unwrap(), Outcome::TooLow);
+        assert_eq!(
+            client.submit(2024, 1, 2, "1,2").unwrap(),
+            Outcome::Wait(Duration::from_secs(83))
+        );
+        assert_eq!(client.submit(2024, 1, 2, "1,2").unwrap(), Outcome::Correct);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/2024/day/1/answer");
+        assert_eq!(requests[0].body, "level=2&answer=31");
+        assert_eq!(requests[2].body, "level=2&answer=1%2C2");
+    }
+
+    #[test]
+    fn test_errors_and_retries() {
+        let (url, requests) = mock_server(vec![
+            (404, NOT_UNLOCKED),
+            (400, LOGGED_OUT),
+            (502, "Bad Gateway"),
+            (200, "<article><p>--- Day 1 ---</p></article>"),
+            (503, ""),
+            (503, ""),
+            (503, ""),
+        ]);
+        let mut client = client(&url);
+
+        assert!(matches!(client.input(2024, 25), Err(Error::NotUnlocked)));
+        assert!(matches!(client.input(2024, 1), Err(Error::Unauthorized)));
+        assert!(client.puzzle(2024, 1).unwrap().contains("--- Day 1 ---"));
+        assert!(matches!(client.puzzle(2024, 1), Err(Error::Status(503))));
+        assert_eq!(requests.lock().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_min_interval() {
+        let (url, _) = mock_server(vec![(200, "a"), (200, "b")]);
+        let mut client = client(&url).with_min_interval(Duration::from_millis(200));
+        let start = Instant::now();
+        client.puzzle(2024, 1).unwrap();
+        client.puzzle(2024, 2).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+}
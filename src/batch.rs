@@ -0,0 +1,107 @@
+//! Solving many inputs for the same puzzle in parallel.
+//!
+//! With the `parallel` feature every solver's `static mut` scratch space is `#[thread_local]`,
+//! so each pool thread gets its own copy and solves don't interfere. That scratch space is
+//! about 6 MiB and lives in each thread's stack allocation, so threads running solvers need
+//! bigger stacks than the default 2 MiB. The pool is built with `STACK_SIZE` stacks.
+
+use crate::dispatch;
+use core::fmt;
+use core::time::Duration;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// Thread-local scratch space plus room for the solvers themselves.
+const STACK_SIZE: usize = 16 << 20;
+
+/// The formatted answer, `None` if there is no such puzzle.
+pub type Answer = Option<String>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Throughput {
+    pub inputs: usize,
+    pub threads: usize,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    pub fn inputs_per_sec(&self) -> f64 {
+        self.inputs as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} inputs in {:?} on {} threads ({:.0} inputs/sec)",
+            self.inputs,
+            self.elapsed,
+            self.threads,
+            self.inputs_per_sec()
+        )
+    }
+}
+
+fn pool() -> &'static ThreadPool {
+    static POOL: OnceLock<ThreadPool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        ThreadPoolBuilder::new()
+            .stack_size(STACK_SIZE)
+            .thread_name(|i| format!("aoc-batch-{}", i))
+            .build()
+            .expect("Failed to build the thread pool")
+    })
+}
+
+/// Solves `day` and `part` for every input on a thread pool. Answers are in input order.
+pub fn solve_batch(day: u32, part: u32, inputs: &[&str]) -> Vec<Answer> {
+    pool().install(|| {
+        inputs
+            .par_iter()
+            .map(|input| dispatch::solve(day, part, input))
+            .collect()
+    })
+}
+
+/// Like [`solve_batch`], also measuring throughput.
+pub fn solve_batch_timed(day: u32, part: u32, inputs: &[&str]) -> (Vec<Answer>, Throughput) {
+    let start = Instant::now();
+    let answers = solve_batch(day, part, inputs);
+    let throughput = Throughput {
+        inputs: inputs.len(),
+        threads: pool().current_num_threads(),
+        elapsed: start.elapsed(),
+    };
+    (answers, throughput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_batch() {
+        // Mix inputs so that threads alternate between different scratch contents
+        let inputs: Vec<&str> = (0..200)
+            .map(|i| match i % 2 {
+                0 => "2333133121414131402\n",
+                _ => "12345\n",
+            })
+            .collect();
+        let (answers, throughput) = solve_batch_timed(9, 1, &inputs);
+        for (i, answer) in answers.iter().enumerate() {
+            let expected = if i % 2 == 0 { "1928" } else { "60" };
+            assert_eq!(answer.as_deref(), Some(expected));
+        }
+        assert_eq!(throughput.inputs, 200);
+        assert!(throughput.threads >= 1);
+    }
+
+    #[test]
+    fn test_unknown_puzzle() {
+        assert_eq!(solve_batch(26, 1, &["", ""]), vec![None, None]);
+    }
+}
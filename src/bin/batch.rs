@@ -0,0 +1,28 @@
+use advent_of_code_2024::batch;
+use std::env;
+use std::fs;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 3 {
+        eprintln!("Usage: batch <day> <part> <input.txt>...");
+        std::process::exit(1);
+    }
+    let day: u32 = args[0].parse().expect("Day must be a number");
+    let part: u32 = args[1].parse().expect("Part must be a number");
+    let inputs: Vec<String> = args[2..]
+        .iter()
+        .map(|path| fs::read_to_string(path).expect("Failed to read the input file"))
+        .collect();
+    let inputs: Vec<&str> = inputs.iter().map(String::as_str).collect();
+
+    let (answers, throughput) = batch::solve_batch_timed(day, part, &inputs);
+    for (path, answer) in args[2..].iter().zip(answers) {
+        println!(
+            "{}: {}",
+            path,
+            answer.as_deref().unwrap_or("no such puzzle")
+        );
+    }
+    println!("{}", throughput);
+}
@@ -0,0 +1,29 @@
+use advent_of_code_2024::examples;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() != 2 {
+        eprintln!("Usage: extract_examples <day> <saved puzzle page.html>");
+        std::process::exit(1);
+    }
+    let day: u32 = args[0].parse().expect("Day must be a number");
+    let html = fs::read_to_string(&args[1]).expect("Failed to read the puzzle page");
+
+    let examples = examples::extract(&html);
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("examples/day{}", day));
+    examples::write_fixtures(&dir, &examples).expect("Failed to write fixtures");
+
+    for (i, example) in examples.iter().enumerate() {
+        println!(
+            "example{}.txt: {} lines, part 1: {}, part 2: {}",
+            i + 1,
+            example.input.lines().count(),
+            example.answers[0].as_deref().unwrap_or("-"),
+            example.answers[1].as_deref().unwrap_or("-")
+        );
+    }
+    println!("Wrote {}, review expected.txt", dir.display());
+}
@@ -0,0 +1,5 @@
+use advent_of_code_2024::ffi;
+
+fn main() {
+    print!("{}", ffi::c_header());
+}
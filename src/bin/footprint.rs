@@ -0,0 +1,234 @@
+//! Reports the memory footprint of each day: the size of every static, the largest stack frame,
+//! and the size of the data sections. Tables meant to show up per day must be `static` items,
+//! anonymous consts only count towards the section totals.
+//!
+//! Builds the `ffi` cdylib, which links every solver, with stack size metadata and reads its
+//! symbols with `nm` and `llvm-readobj`. The result is compared against `footprint.txt`
+//! (or the `footprint.txt` of a given commit), and any growth or a missing baseline makes the
+//! tool exit with an error. The build targets `TARGET_CPU` rather than the host CPU, so the
+//! baseline does not depend on the machine it was measured on.
+//!
+//! ```shell
+//! cargo run --bin footprint                      # compare against ./footprint.txt
+//! cargo run --bin footprint -- --baseline HEAD~1 # compare against a commit
+//! cargo run --bin footprint -- --update          # accept the current footprint
+//! ```
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::process::{self, Command};
+
+const CRATE: &str = "advent_of_code_2024::";
+const BASELINE: &str = "footprint.txt";
+const TARGET_DIR: &str = "target/footprint";
+const TARGET_CPU: &str = "x86-64-v3";
+const SECTIONS: [&str; 4] = [".text", ".rodata", ".data", ".bss"];
+
+/// `"static day16::FORWARD_QUEUE"`, `"stack day16"` or `"section .rodata"` to a size in bytes.
+type Footprint = BTreeMap<String, u64>;
+
+fn main() {
+    let mut artifact = None;
+    let mut baseline = BASELINE.to_string();
+    let mut update = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--artifact" => artifact = args.next(),
+            "--baseline" => baseline = args.next().expect("--baseline needs a file or commit"),
+            "--update" => update = true,
+            _ => {
+                eprintln!(
+                    "Usage: footprint [--artifact <lib.so>] [--baseline <file|commit>] [--update]"
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    let artifact = artifact.unwrap_or_else(build);
+    let current = measure(&artifact);
+    print_report(&current);
+
+    if update {
+        fs::write(BASELINE, serialize(&current)).expect("Failed to write the baseline");
+        println!("Wrote {}", BASELINE);
+        return;
+    }
+    let Some(previous) = load_baseline(&baseline) else {
+        eprintln!(
+            "No baseline at {}, run with --update to create one",
+            baseline
+        );
+        process::exit(1);
+    };
+    if compare(&previous, &current) {
+        eprintln!("Footprint grew, run with --update if this is intended");
+        process::exit(1);
+    }
+}
+
+fn build() -> String {
+    let status = Command::new("cargo")
+        .args(["rustc", "--release", "--lib", "--features", "ffi"])
+        .args(["--crate-type", "cdylib", "--target-dir", TARGET_DIR])
+        .env(
+            "CARGO_ENCODED_RUSTFLAGS",
+            format!("-Ctarget-cpu={}\x1f-Zemit-stack-sizes", TARGET_CPU),
+        )
+        .status()
+        .expect("Failed to run cargo");
+    assert!(status.success(), "Build failed");
+    format!("{}/release/libadvent_of_code_2024.so", TARGET_DIR)
+}
+
+fn run(program: &str, args: &[&str]) -> String {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .unwrap_or_else(|e| panic!("Failed to run {}: {}", program, e));
+    assert!(output.status.success(), "{} failed", program);
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn measure(artifact: &str) -> Footprint {
+    let mut footprint = Footprint::new();
+
+    // `<addr> <size> <type> <name>`, data in b/d/r (lowercase for local symbols)
+    for line in run("nm", &["-S", "-C", artifact]).lines() {
+        let fields: Vec<&str> = line.splitn(4, ' ').collect();
+        let [_, size, kind, name] = fields[..] else {
+            continue;
+        };
+        let Some(name) = name.strip_prefix(CRATE) else {
+            continue;
+        };
+        if "bBdDrR".contains(kind) {
+            let size = u64::from_str_radix(size, 16).unwrap();
+            *footprint.entry(format!("static {}", name)).or_default() += size;
+        }
+    }
+
+    // Entries of `Functions: [<name>]` followed by `Size: 0x<frame size>`
+    let stack_sizes = run("llvm-readobj", &["--stack-sizes", "--demangle", artifact]);
+    let mut function = None;
+    for line in stack_sizes.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("Functions: [") {
+            function = name
+                .trim_end_matches(']')
+                .strip_prefix(CRATE)
+                .map(str::to_string);
+        } else if let (Some(size), Some(name)) = (line.strip_prefix("Size: 0x"), &function) {
+            let module = name.split("::").next().unwrap();
+            let frame = footprint.entry(format!("stack {}", module)).or_default();
+            *frame = (*frame).max(u64::from_str_radix(size, 16).unwrap());
+        }
+    }
+
+    // `<section> <size> <addr>`
+    for line in run("size", &["-A", artifact]).lines() {
+        let mut fields = line.split_whitespace();
+        if let (Some(section), Some(size)) = (fields.next(), fields.next()) {
+            if SECTIONS.contains(&section) {
+                footprint.insert(format!("section {}", section), size.parse().unwrap());
+            }
+        }
+    }
+
+    footprint
+}
+
+fn print_report(footprint: &Footprint) {
+    // module -> (total static bytes, largest static, largest stack frame)
+    let mut modules: BTreeMap<&str, (u64, (&str, u64), u64)> = BTreeMap::new();
+    for (key, &size) in footprint {
+        let (kind, name) = key.split_once(' ').unwrap();
+        let module = name.split("::").next().unwrap();
+        match kind {
+            "static" => {
+                let entry = modules.entry(module).or_default();
+                entry.0 += size;
+                if size > entry.1 .1 {
+                    entry.1 = (name, size);
+                }
+            }
+            "stack" => modules.entry(module).or_default().2 = size,
+            _ => {}
+        }
+    }
+
+    println!(
+        "{:<10} {:>12} {:>12}  largest static",
+        "module", "statics", "max frame"
+    );
+    let mut modules: Vec<_> = modules.into_iter().collect();
+    modules.sort_by_key(|&(module, _)| {
+        let day = module.strip_prefix("day").and_then(|d| d.parse().ok());
+        (day.unwrap_or(u32::MAX), module)
+    });
+    for (module, (statics, (largest, largest_size), frame)) in modules {
+        println!(
+            "{:<10} {:>12} {:>12}  {} ({})",
+            module, statics, frame, largest, largest_size
+        );
+    }
+    for section in SECTIONS {
+        if let Some(size) = footprint.get(&format!("section {}", section)) {
+            println!("{:<10} {:>12}", section, size);
+        }
+    }
+}
+
+fn serialize(footprint: &Footprint) -> String {
+    footprint
+        .iter()
+        .map(|(key, size)| format!("{} {}\n", key, size))
+        .collect()
+}
+
+fn parse(text: &str) -> Footprint {
+    text.lines()
+        .filter_map(|line| {
+            let (key, size) = line.rsplit_once(' ')?;
+            Some((key.to_string(), size.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Reads the baseline from a file, or from `footprint.txt` at a commit.
+fn load_baseline(baseline: &str) -> Option<Footprint> {
+    if let Ok(text) = fs::read_to_string(baseline) {
+        return Some(parse(&text));
+    }
+    let output = Command::new("git")
+        .args(["show", &format!("{}:{}", baseline, BASELINE)])
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Prints every difference, returns whether anything grew or appeared.
+fn compare(previous: &Footprint, current: &Footprint) -> bool {
+    let mut grew = false;
+    let keys: std::collections::BTreeSet<&String> = previous.keys().chain(current.keys()).collect();
+    for key in keys {
+        match (previous.get(key), current.get(key)) {
+            (Some(old), Some(new)) if old != new => {
+                let delta = *new as i64 - *old as i64;
+                println!("{:<40} {:>12} -> {:>12} ({:+})", key, old, new, delta);
+                grew |= delta > 0;
+            }
+            (None, Some(new)) => {
+                println!("{:<40} {:>12} (new)", key, new);
+                grew |= *new > 0;
+            }
+            (Some(old), None) => println!("{:<40} {:>12} (removed)", key, old),
+            _ => {}
+        }
+    }
+    grew
+}
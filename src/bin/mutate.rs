@@ -0,0 +1,32 @@
+use advent_of_code_2024::{dispatch, mutate};
+use std::env;
+use std::fs;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 3 {
+        eprintln!("Usage: mutate <day> <input.txt> <output.txt> [seed]");
+        std::process::exit(1);
+    }
+    let day: u32 = args[0].parse().expect("Day must be a number");
+    let seed: u64 = args
+        .get(3)
+        .map_or(1, |s| s.parse().expect("Seed must be a number"));
+    let input = fs::read_to_string(&args[1]).expect("Failed to read the input file");
+
+    let Some(mutation) = mutate::mutate(day, &input, seed) else {
+        eprintln!("No mutator for day {}", day);
+        std::process::exit(1);
+    };
+    fs::write(&args[2], &mutation.input).expect("Failed to write the output file");
+
+    for part in 1..=2 {
+        let expected = if mutation.is_recomputed(part) {
+            mutation.expected(part, "")
+        } else {
+            let original = dispatch::solve(day, part, &input).expect("No solver");
+            mutation.expected(part, &original)
+        };
+        println!("Part {}: {}", part, expected);
+    }
+}
@@ -0,0 +1,41 @@
+use advent_of_code_2024::{dispatch, trace};
+use std::env;
+use std::fs;
+
+const ITERATIONS: usize = 10;
+const OUTPUT: &str = "trace.json";
+
+fn main() {
+    // Days to trace, all of them by default
+    let mut days: Vec<u32> = env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("Days must be numbers"))
+        .collect();
+    if days.is_empty() {
+        days = (1..=25).collect();
+    }
+
+    let input_dir = format!("{}/input/2024", env!("CARGO_MANIFEST_DIR"));
+    for day in days {
+        let path = format!("{}/day{}.txt", input_dir, day);
+        let input = match fs::read_to_string(&path) {
+            Ok(input) if !input.is_empty() => input,
+            _ => {
+                println!("Skipping day {}: no input at {}", day, path);
+                continue;
+            }
+        };
+
+        for part in 1..=2 {
+            for _ in 0..ITERATIONS {
+                dispatch::solve(day, part, &input);
+            }
+        }
+    }
+
+    let events = trace::take_events();
+    let file = fs::File::create(OUTPUT).expect("Failed to create trace file");
+    trace::write_chrome_trace(std::io::BufWriter::new(file), &events)
+        .expect("Failed to write trace file");
+    println!("Wrote {} spans to {}", events.len(), OUTPUT);
+}
@@ -6,7 +6,7 @@ fn main() {
 
     // Run benchmarks
     let output = Command::new("cargo")
-        .args(&["bench"])
+        .args(&["bench", "--bench", "main"])
         .output()
         .expect("Failed to run benchmarks");
 
@@ -1,15 +1,28 @@
+use crate::radix;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
 
 // Input
 const CHUNK_SIZE: usize = 14;
 const TOTAL_LINES: usize = 1000;
 const COUNTS_SIZE: usize = 90_000;
-static mut COUNTS: [u8; COUNTS_SIZE] = [0; COUNTS_SIZE];
+// u16 as a location ID can appear on every line
+#[cfg_attr(feature = "parallel", thread_local)]
+static mut COUNTS: [u16; COUNTS_SIZE] = [0; COUNTS_SIZE];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut FIRST_NUMBERS: [u32; TOTAL_LINES] = [0; TOTAL_LINES];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut SECOND_NUMBERS: [u32; TOTAL_LINES] = [0; TOTAL_LINES];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut FIRST_NUMBERS_PART2: [u32; TOTAL_LINES] = [0; TOTAL_LINES];
 // Generation
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut LAST_UPDATED: [u32; COUNTS_SIZE] = [0; COUNTS_SIZE];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut GENERATION: u32 = 1;
 // Radix
 const LOWER_BITS: usize = 9;
@@ -19,7 +32,7 @@ const SECOND_MASK: usize = (1 << UPPER_BITS) - 1; // 0xFF
 const LOWER_SIZE: usize = 1 << LOWER_BITS; // 512
 const UPPER_SIZE: usize = 1 << UPPER_BITS; // 256
 
-#[aoc(day1, part1, naive)]
+#[cfg_attr(feature = "std", aoc(day1, part1, naive))]
 pub fn part1_naive(input: &str) -> u64 {
     let (mut list1, mut list2) = get_lists(input);
     list1.sort_unstable();
@@ -31,7 +44,7 @@ pub fn part1_naive(input: &str) -> u64 {
         .sum()
 }
 
-#[aoc(day1, part2, naive)]
+#[cfg_attr(feature = "std", aoc(day1, part2, naive))]
 pub fn part2_naive(input: &str) -> u64 {
     let (list1, list2) = get_lists(input);
     let occurrences = list2.iter().fold(HashMap::new(), |mut acc, &num| {
@@ -91,15 +104,58 @@ fn radix_sort_two_pass(data: &mut [u32]) {
     }
 }
 
+/// Whether the input is exactly `TOTAL_LINES` lines of two 5-digit numbers 3 spaces apart,
+/// which the fixed-offset parser below relies on. Neither number may start with a zero:
+/// `part2` indexes its tables with `num - 10_000`.
+fn is_fixed_layout(bytes: &[u8]) -> bool {
+    let is_5_digit_number = |num: &[u8]| num[0] != b'0' && num.iter().all(|b| b.is_ascii_digit());
+    (bytes.len() == TOTAL_LINES * CHUNK_SIZE || bytes.len() == TOTAL_LINES * CHUNK_SIZE - 1)
+        && bytes.chunks(CHUNK_SIZE).all(|line| {
+            is_5_digit_number(&line[0..5])
+                && line[5..8] == *b"   "
+                && is_5_digit_number(&line[8..13])
+                && line.get(13).is_none_or(|&b| b == b'\n')
+        })
+}
+
+/// Parses two columns of numbers of any width, separated by any run of spaces or tabs.
+/// Returns both columns and the largest value in each.
+fn parse_columns(bytes: &[u8]) -> ([Vec<u32>; 2], [u32; 2]) {
+    let mut columns = [Vec::new(), Vec::new()];
+    let mut max = [0; 2];
+    let mut column = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let mut num = 0u32;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            num = num * 10 + (bytes[i] - b'0') as u32;
+            i += 1;
+        }
+        columns[column].push(num);
+        max[column] = max[column].max(num);
+        column ^= 1;
+    }
+    (columns, max)
+}
+
 #[allow(static_mut_refs)]
-#[aoc(day1, part1)]
-pub fn part1(input: &str) -> u32 {
+#[cfg_attr(feature = "std", aoc(day1, part1))]
+pub fn part1(input: &str) -> u64 {
+    trace_span!("day1::part1");
     let bytes = input.as_bytes();
+    if !is_fixed_layout(bytes) {
+        return part1_general(bytes);
+    }
 
-    let mut sum: u32 = 0;
+    let mut sum: u64 = 0;
     let mut offset = 0;
 
     unsafe {
+        trace_phase!("parse");
         for n in 0..TOTAL_LINES {
             let num1 = parse_5_digit_number(bytes.get_unchecked(offset..offset + 5));
             let num2 = parse_5_digit_number(bytes.get_unchecked(offset + 8..offset + 13));
@@ -110,30 +166,53 @@ pub fn part1(input: &str) -> u32 {
             offset += CHUNK_SIZE;
         }
 
+        trace_phase!("sort");
         radix_sort_two_pass(&mut FIRST_NUMBERS);
         radix_sort_two_pass(&mut SECOND_NUMBERS);
 
+        trace_phase!("reduce");
         for i in 0..TOTAL_LINES {
             let a = *FIRST_NUMBERS.get_unchecked(i);
             let b = *SECOND_NUMBERS.get_unchecked(i);
-            sum += a.abs_diff(b);
+            sum += a.abs_diff(b) as u64;
         }
     }
 
     sum
 }
 
+fn part1_general(bytes: &[u8]) -> u64 {
+    trace_phase!("parse");
+    let ([mut first, mut second], _) = parse_columns(bytes);
+
+    trace_phase!("sort");
+    radix::sort(&mut first);
+    radix::sort(&mut second);
+
+    trace_phase!("reduce");
+    first
+        .iter()
+        .zip(&second)
+        .map(|(&a, &b)| a.abs_diff(b) as u64)
+        .sum()
+}
+
 #[allow(static_mut_refs)]
-#[aoc(day1, part2)]
-pub fn part2(input: &str) -> u32 {
+#[cfg_attr(feature = "std", aoc(day1, part2))]
+pub fn part2(input: &str) -> u64 {
+    trace_span!("day1::part2");
     let bytes = input.as_bytes();
+    if !is_fixed_layout(bytes) {
+        return part2_general(bytes);
+    }
 
-    let mut sum: u32 = 0;
+    let mut sum: u64 = 0;
     let mut offset = 0;
 
     unsafe {
         GENERATION = GENERATION.wrapping_add(1);
 
+        trace_phase!("build tables");
         for n in 0..TOTAL_LINES {
             let num1 = parse_5_digit_number(bytes.get_unchecked(offset..offset + 5));
             let num2 = parse_5_digit_number(bytes.get_unchecked(offset + 8..offset + 13));
@@ -152,12 +231,13 @@ pub fn part2(input: &str) -> u32 {
             offset += CHUNK_SIZE;
         }
 
+        trace_phase!("reduce");
         for n in 0..TOTAL_LINES {
             let num = *FIRST_NUMBERS_PART2.get_unchecked(n);
             let index = (num - 10_000) as usize;
 
             if *LAST_UPDATED.get_unchecked(index) == GENERATION {
-                sum += (*COUNTS.get_unchecked(index) as u32) * num;
+                sum += *COUNTS.get_unchecked(index) as u64 * num as u64;
             }
         }
     }
@@ -165,6 +245,250 @@ pub fn part2(input: &str) -> u32 {
     sum
 }
 
+fn part2_general(bytes: &[u8]) -> u64 {
+    trace_phase!("parse");
+    let ([mut first, mut second], [_, max2]) = parse_columns(bytes);
+    let min2 = second.iter().copied().min().unwrap_or(0);
+    let range = (max2 - min2) as usize + 1;
+
+    if range <= COUNTS_SIZE.max(second.len()) {
+        // Dense enough for a table of counts indexed by value
+        trace_phase!("build tables");
+        let mut counts = vec![0u32; range];
+        for &num in &second {
+            counts[(num - min2) as usize] += 1;
+        }
+
+        trace_phase!("reduce");
+        return first
+            .iter()
+            .filter_map(|&num| {
+                let count = counts.get(num.checked_sub(min2)? as usize)?;
+                Some(*count as u64 * num as u64)
+            })
+            .sum();
+    }
+
+    // Too spread out for a table: sort both and count matches by merging
+    trace_phase!("sort");
+    radix::sort(&mut first);
+    radix::sort(&mut second);
+
+    trace_phase!("reduce");
+    let mut sum = 0u64;
+    let (mut lo, mut hi) = (0, 0);
+    for &num in &first {
+        while lo < second.len() && second[lo] < num {
+            lo += 1;
+        }
+        hi = hi.max(lo);
+        while hi < second.len() && second[hi] == num {
+            hi += 1;
+        }
+        sum += num as u64 * (hi - lo) as u64;
+    }
+    sum
+}
+
+/// Both day 1 answers over a changing pair of lists: the similarity score and the total
+/// distance between the sorted lists, paired by rank up to the length of the shorter list.
+///
+/// Location IDs are bounded by `max_id`, counts per ID are kept in tables like `COUNTS`, so each
+/// list allocates two tables of `max_id + 1` counters (about 800 KB for the 5 digit IDs).
+/// The score is maintained in O(1) per update. The distance uses Fenwick trees as
+/// order-statistics over the IDs: an update at rank `r` shifts the ranks after it by one, which
+/// only gives a rank another partner where the ID changes. So one pair is re-paired per run of
+/// equal IDs after `r`, plus the pair gained or lost at the end of the shorter list, each looked
+/// up in O(log max_id).
+pub struct SimilarityIndex {
+    left: OrderStatistics,
+    right: OrderStatistics,
+    score: u64,
+    distance: u64,
+}
+
+impl SimilarityIndex {
+    pub fn new(max_id: u32) -> Self {
+        Self {
+            left: OrderStatistics::new(max_id),
+            right: OrderStatistics::new(max_id),
+            score: 0,
+            distance: 0,
+        }
+    }
+
+    /// Part 2 of the current lists.
+    #[inline(always)]
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    /// Part 1 of the current lists.
+    #[inline(always)]
+    pub fn distance(&self) -> u64 {
+        self.distance
+    }
+
+    pub fn len_left(&self) -> usize {
+        self.left.len
+    }
+
+    pub fn len_right(&self) -> usize {
+        self.right.len
+    }
+
+    pub fn insert_left(&mut self, id: u32) {
+        self.score += id as u64 * self.right.count(id) as u64;
+        self.distance = repair(self.distance, &self.left, &self.right, id, true);
+        self.left.add(id, 1);
+    }
+
+    pub fn insert_right(&mut self, id: u32) {
+        self.score += id as u64 * self.left.count(id) as u64;
+        self.distance = repair(self.distance, &self.right, &self.left, id, true);
+        self.right.add(id, 1);
+    }
+
+    /// Returns `false` if `id` isn't in the left list.
+    pub fn remove_left(&mut self, id: u32) -> bool {
+        if self.left.count(id) == 0 {
+            return false;
+        }
+        self.score -= id as u64 * self.right.count(id) as u64;
+        self.distance = repair(self.distance, &self.left, &self.right, id, false);
+        self.left.add(id, -1);
+        true
+    }
+
+    /// Returns `false` if `id` isn't in the right list.
+    pub fn remove_right(&mut self, id: u32) -> bool {
+        if self.right.count(id) == 0 {
+            return false;
+        }
+        self.score -= id as u64 * self.left.count(id) as u64;
+        self.distance = repair(self.distance, &self.right, &self.left, id, false);
+        self.right.add(id, -1);
+        true
+    }
+}
+
+/// The distance after inserting `id` into `list` or removing it, called before the change.
+///
+/// `id` goes to (or leaves from) `start`, the first rank of its run. Inserting moves every later
+/// run of equal IDs up one rank, so only each run's first rank gets another ID. Removing moves
+/// them down, so only each run's last rank does.
+fn repair(
+    distance: u64,
+    list: &OrderStatistics,
+    other: &OrderStatistics,
+    id: u32,
+    insert: bool,
+) -> u64 {
+    let pair = |id: u32, rank: usize| id.abs_diff(other.select(rank)) as u64;
+    let start = list.rank(id);
+    let old_end = list.len.min(other.len);
+    let (mut added, mut removed) = (0, 0);
+
+    if insert {
+        let (mut rank, mut prev) = (start, id);
+        while rank < old_end {
+            let cur = list.select(rank);
+            added += pair(prev, rank);
+            removed += pair(cur, rank);
+            prev = cur;
+            rank += list.count(cur) as usize;
+        }
+        if list.len < other.len {
+            // `prev` ended up at rank `old_end` unless the insert was after it
+            let last = if old_end < start {
+                list.select(old_end)
+            } else {
+                prev
+            };
+            added += pair(last, old_end);
+        }
+    } else {
+        let new_end = (list.len - 1).min(other.len);
+        let mut rank = start;
+        while rank < new_end {
+            let cur = list.select(rank);
+            let last = rank + list.count(cur) as usize - 1;
+            if last >= new_end {
+                break;
+            }
+            let next = list.select(last + 1);
+            added += pair(next, last);
+            removed += pair(cur, last);
+            rank = last + 1;
+        }
+        if new_end < old_end {
+            removed += pair(list.select(new_end), new_end);
+        }
+    }
+    distance + added - removed
+}
+
+/// Multiset of IDs in `0..=max_id` with counts per ID and a Fenwick tree for rank/select.
+struct OrderStatistics {
+    counts: Vec<u32>,
+    tree: Vec<u32>,
+    len: usize,
+}
+
+impl OrderStatistics {
+    fn new(max_id: u32) -> Self {
+        let size = max_id as usize + 1;
+        Self {
+            counts: vec![0; size],
+            tree: vec![0; size + 1],
+            len: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn count(&self, id: u32) -> u32 {
+        self.counts[id as usize]
+    }
+
+    fn add(&mut self, id: u32, delta: i32) {
+        let count = &mut self.counts[id as usize];
+        *count = count.wrapping_add_signed(delta);
+        self.len = self.len.wrapping_add_signed(delta as isize);
+        let mut i = id as usize + 1;
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i].wrapping_add_signed(delta);
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Number of IDs smaller than `id`.
+    fn rank(&self, id: u32) -> usize {
+        let mut sum = 0;
+        let mut i = id as usize;
+        while i > 0 {
+            sum += self.tree[i] as usize;
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// The ID at `rank` in sorted order, `rank < len`.
+    fn select(&self, rank: usize) -> u32 {
+        let mut pos = 0;
+        let mut remaining = rank;
+        let mut step = (self.tree.len() - 1).next_power_of_two();
+        while step > 0 {
+            let next = pos + step;
+            if next < self.tree.len() && (self.tree[next] as usize) <= remaining {
+                pos = next;
+                remaining -= self.tree[next] as usize;
+            }
+            step >>= 1;
+        }
+        pos as u32
+    }
+}
+
 #[inline(always)]
 fn parse_5_digit_number(slice: &[u8]) -> u32 {
     unsafe {
@@ -193,7 +517,8 @@ fn get_lists(input: &str) -> (Vec<u64>, Vec<u64>) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::Rng;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     const TEST_INPUT: &str = "3   4
 4   3
@@ -212,9 +537,143 @@ mod tests {
         assert_eq!(part2_naive(TEST_INPUT), 31);
     }
 
+    #[test]
+    fn test_fast_example() {
+        assert_eq!(part1(TEST_INPUT), 11);
+        assert_eq!(part2(TEST_INPUT), 31);
+        assert_eq!(part1("3\t4\r\n4 3\n"), 0);
+    }
+
+    #[test]
+    fn test_general_matches_naive() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for (lines, max) in [
+            (1, 9),
+            (10, 99),
+            (1000, 99_999),
+            (2500, 1 << 20),
+            (3000, u32::MAX),
+        ] {
+            let input: String = (0..lines)
+                .map(|_| {
+                    let sep = " ".repeat(rng.gen_range(1..5));
+                    // Small values repeat, so part 2 has matches to count
+                    let a = rng.gen_range(0..=max.min(50));
+                    let b = rng.gen_range(0..=max);
+                    format!("{a}{sep}{b}\n{b}{sep}{a}\n")
+                })
+                .collect();
+            assert_eq!(
+                part1(&input),
+                part1_naive(&input),
+                "{lines} lines up to {max}"
+            );
+            assert_eq!(
+                part2(&input),
+                part2_naive(&input),
+                "{lines} lines up to {max}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixed_layout_matches_naive() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let input: String = (0..TOTAL_LINES)
+            .map(|_| {
+                let a = rng.gen_range(10_000..=99_999);
+                let b = rng.gen_range(10_000..=10_500);
+                format!("{a}   {b}\n")
+            })
+            .collect();
+        assert!(is_fixed_layout(input.as_bytes()));
+        assert!(!is_fixed_layout(TEST_INPUT.as_bytes()));
+        assert_eq!(part1(&input), part1_naive(&input));
+        assert_eq!(part2(&input), part2_naive(&input));
+
+        // Same width, but not 5-digit numbers the tables can be indexed with
+        for line in ["  123   00001\n", "12345   01234\n"] {
+            let input = line.repeat(TOTAL_LINES);
+            assert!(!is_fixed_layout(input.as_bytes()));
+            assert_eq!(part1(&input), part1_naive(&input));
+            assert_eq!(part2(&input), part2_naive(&input));
+        }
+
+        // Sums past u32::MAX
+        let input = "99999   99999\n".repeat(250) + &"10000   10001\n".repeat(TOTAL_LINES - 250);
+        assert!(is_fixed_layout(input.as_bytes()));
+        assert_eq!(part1(&input), part1_naive(&input));
+        assert_eq!(part2(&input), 250 * 250 * 99_999);
+    }
+
+    #[test]
+    fn test_similarity_index_example() {
+        let mut index = SimilarityIndex::new(9);
+        for (a, b) in [(3, 4), (4, 3), (2, 5), (1, 3), (3, 9), (3, 3)] {
+            index.insert_left(a);
+            index.insert_right(b);
+        }
+        assert_eq!(index.distance(), 11);
+        assert_eq!(index.score(), 31);
+
+        assert!(index.remove_right(3));
+        assert!(!index.remove_left(9));
+        assert_eq!((index.len_left(), index.len_right()), (6, 5));
+        assert_eq!(index.score(), 31 - 3 * 3);
+    }
+
+    #[test]
+    fn test_similarity_index_random_updates() {
+        // Few IDs for long runs of equal IDs, many for mostly distinct ones
+        for max_id in [99, 99_999] {
+            similarity_index_random_updates(max_id);
+        }
+    }
+
+    fn similarity_index_random_updates(max_id: u32) {
+        let mut rng = StdRng::seed_from_u64(max_id as u64);
+        let mut index = SimilarityIndex::new(max_id);
+        let (mut left, mut right): (Vec<u32>, Vec<u32>) = (Vec::new(), Vec::new());
+        for _ in 0..3000 {
+            let id = rng.gen_range(0..=max_id);
+            let is_left = rng.gen_bool(0.5);
+            let side = if is_left { &mut left } else { &mut right };
+            if rng.gen_bool(0.6) {
+                side.push(id);
+                if is_left {
+                    index.insert_left(id);
+                } else {
+                    index.insert_right(id);
+                }
+            } else {
+                let present = side
+                    .iter()
+                    .position(|&x| x == id)
+                    .map(|i| side.swap_remove(i));
+                let removed = if is_left {
+                    index.remove_left(id)
+                } else {
+                    index.remove_right(id)
+                };
+                assert_eq!(removed, present.is_some());
+            }
+
+            let (mut l, mut r) = (left.clone(), right.clone());
+            l.sort_unstable();
+            r.sort_unstable();
+            let distance: u64 = l.iter().zip(&r).map(|(a, b)| a.abs_diff(*b) as u64).sum();
+            let score: u64 = l
+                .iter()
+                .map(|&a| a as u64 * r.iter().filter(|&&b| b == a).count() as u64)
+                .sum();
+            assert_eq!(index.distance(), distance);
+            assert_eq!(index.score(), score);
+        }
+    }
+
     #[test]
     fn test_radix_sort_random() {
-        let mut rng = rand::thread_rng();
+        let mut rng = StdRng::seed_from_u64(3);
         for _ in 0..100 {
             let mut data: Vec<u32> = (0..1000).map(|_| rng.gen_range(10_000..=99_999)).collect();
             let mut expected = data.clone();
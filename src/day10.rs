@@ -1,23 +1,27 @@
 use memchr::memchr_iter;
 
-#[aoc(day10, part1)]
+#[cfg_attr(feature = "std", aoc(day10, part1))]
 pub fn part1(input: &str) -> u16 {
+    trace_span!("day10::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
-#[aoc(day10, part2)]
+#[cfg_attr(feature = "std", aoc(day10, part2))]
 pub fn part2(input: &str) -> u16 {
+    trace_span!("day10::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
 /// Assume grid is max 64x64
-const MEMO_SIZE: usize = 64 * 64;
+pub(crate) const MEMO_SIZE: usize = 64 * 64;
 
 unsafe fn part1_impl(grid: &[u8]) -> u16 {
     let line_len = get_line_len(&grid);
     let mut memo: [u16; MEMO_SIZE] = [u16::MAX; MEMO_SIZE];
     let mut total_paths: u16 = 0;
 
+    trace_phase!("search");
+
     // Iterate over all '9's and perform DFS to count paths back to '0's.
     for pos in memchr_iter(b'9', grid) {
         let id = pos as u16;
@@ -33,6 +37,8 @@ unsafe fn part2_impl(grid: &[u8]) -> u16 {
     let mut memo: [u16; MEMO_SIZE] = [0; MEMO_SIZE];
     let mut total_paths: u16 = 0;
 
+    trace_phase!("search");
+
     // Iterate over all '9's and perform DFS to count paths back to '0's.
     for pos in memchr_iter(b'9', grid) {
         total_paths += dfs9_memo(pos, grid, &mut memo, line_len, grid.len());
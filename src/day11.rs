@@ -1,17 +1,21 @@
-use std::mem::transmute;
+#[cfg(not(feature = "lean"))]
+use core::mem::transmute;
 
-#[aoc(day11, part1)]
+#[cfg_attr(feature = "std", aoc(day11, part1))]
 pub fn part1(input: &str) -> u64 {
+    trace_span!("day11::part1");
     unsafe { solve::<Lut1Provider, { MAX_BLINKS - 25 }>(input.as_bytes()) }
 }
 
-#[aoc(day11, part2)]
+#[cfg_attr(feature = "std", aoc(day11, part2))]
 pub fn part2(input: &str) -> u64 {
+    trace_span!("day11::part2");
     unsafe { solve::<Lut2Provider, 0>(input.as_bytes()) }
 }
 
 const DIGIT_THRESHOLD: u8 = b'0' - 1;
 
+#[allow(clippy::absurd_extreme_comparisons)] // BIN_LUT_SIZE is 0 in lean builds
 unsafe fn solve<P: LutProvider, const START_FROM: usize>(input: &[u8]) -> u64 {
     let mut sum = 0;
     let mut n: usize = 0;
@@ -20,6 +24,8 @@ unsafe fn solve<P: LutProvider, const START_FROM: usize>(input: &[u8]) -> u64 {
     let end = current.add(input.len());
     let lut_ptr = P::LUT.as_ptr();
 
+    trace_phase!("lookup");
+
     while current < end {
         if *current > DIGIT_THRESHOLD {
             n = n * 10 + (*current - b'0') as usize;
@@ -86,7 +92,11 @@ const fn split_number(n: u64) -> (u64, u64) {
     (left, right)
 }
 
+#[cfg(not(feature = "lean"))]
 const BIN_LUT_SIZE: usize = 100_000; // Increase to 10_000_000 for slower build but faster runtime
+/// Lean builds skip the big LUTs, so every stone resolves through the mini LUT.
+#[cfg(feature = "lean")]
+const BIN_LUT_SIZE: usize = 0;
 const MINI_LUT_SIZE: usize = 1000;
 const MINI_LUT_BOUND: u64 = MINI_LUT_SIZE as u64;
 const MAX_BLINKS: usize = 75;
@@ -96,18 +106,25 @@ const MAX_BLINKS: usize = 75;
 /// Mini version doesn't contain that many stone numbers, but covers all blinks.
 /// This is used as a fallback if any input numbers are above 7 digits.
 #[allow(long_running_const_eval)]
-const MINI_LUT: AlignedMiniLUT = AlignedMiniLUT(compute_mini_lut());
+static MINI_LUT: AlignedMiniLUT = AlignedMiniLUT(compute_mini_lut());
 
 #[repr(align(64))]
 struct AlignedMiniLUT([[u64; MINI_LUT_SIZE]; MAX_BLINKS]);
 
+#[cfg(not(feature = "lean"))]
 #[allow(long_running_const_eval)]
-const BIG_LUT1: AlignedBigLUT =
+static BIG_LUT1: AlignedBigLUT =
     unsafe { transmute(*include_bytes!(concat!(env!("OUT_DIR"), "/day11lut1.bin"))) };
+#[cfg(not(feature = "lean"))]
 #[allow(long_running_const_eval)]
-const BIG_LUT2: AlignedBigLUT =
+static BIG_LUT2: AlignedBigLUT =
     unsafe { transmute(*include_bytes!(concat!(env!("OUT_DIR"), "/day11lut2.bin"))) };
+#[cfg(feature = "lean")]
+static BIG_LUT1: AlignedBigLUT = AlignedBigLUT([]);
+#[cfg(feature = "lean")]
+static BIG_LUT2: AlignedBigLUT = AlignedBigLUT([]);
 
+/// Statics rather than consts, so the footprint report attributes them to day 11.
 #[repr(align(64))]
 struct AlignedBigLUT([u64; BIN_LUT_SIZE]);
 
@@ -1,20 +1,22 @@
-#[aoc(day12, part1)]
+#[cfg_attr(feature = "std", aoc(day12, part1))]
 pub fn part1(input: &str) -> u32 {
+    trace_span!("day12::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
-const MAX_SIZE: usize = 140;
+pub(crate) const MAX_SIZE: usize = 140;
 const MAX_LINE_LEN: usize = MAX_SIZE + 1; // Add \n
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut VISITED: [u8; MAX_SIZE * MAX_LINE_LEN] = [0; MAX_SIZE * MAX_LINE_LEN];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u8 = 0;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut STACK: [usize; MAX_SIZE * MAX_LINE_LEN] = [0; MAX_SIZE * MAX_LINE_LEN];
 
 #[inline(always)]
 fn compute_height(total_len: usize) -> usize {
-    let discriminant = 1 + 4 * total_len;
-    let sqrt_discriminant = (discriminant as f64).sqrt();
-    let height = (-1.0 + sqrt_discriminant) / 2.0;
-    height.round() as usize
+    // total_len = height * (height + 1), so height is its integer square root
+    total_len.isqrt()
 }
 
 #[allow(static_mut_refs)]
@@ -32,6 +34,7 @@ unsafe fn part1_impl(grid: &[u8]) -> u32 {
     let mut stack_top = stack_ptr;
     let mut total_price: u32 = 0;
 
+    trace_phase!("search");
     let mut i: usize = 0;
     while i < total_len {
         let current_char = *grid_ptr.add(i);
@@ -109,8 +112,9 @@ unsafe fn part1_impl(grid: &[u8]) -> u32 {
     total_price
 }
 
-#[aoc(day12, part2)]
+#[cfg_attr(feature = "std", aoc(day12, part2))]
 pub fn part2(input: &str) -> u32 {
+    trace_span!("day12::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
@@ -131,6 +135,7 @@ unsafe fn part2_impl(grid: &[u8]) -> u32 {
 
     let directions: [isize; 4] = [-1, 1, -(line_len as isize), line_len as isize];
 
+    trace_phase!("search");
     let mut i: usize = 0;
     while i < total_len {
         let current_char = *grid_ptr.add(i);
@@ -1,20 +1,24 @@
-#[aoc(day13, part1)]
+#[cfg_attr(feature = "std", aoc(day13, part1))]
 pub fn part1(input: &str) -> i32 {
+    trace_span!("day13::part1");
     unsafe { part1_scalar_impl(input.as_bytes()) }
 }
 
-#[aoc(day13, part2)]
+#[cfg_attr(feature = "std", aoc(day13, part2))]
 pub fn part2(input: &str) -> i64 {
+    trace_span!("day13::part2");
     unsafe { part2_scalar_impl(input.as_bytes()) }
 }
 
-const NUM_EQUATIONS: usize = 320;
+pub(crate) const NUM_EQUATIONS: usize = 320;
 
 unsafe fn part1_scalar_impl(input: &[u8]) -> i32 {
     let mut sum: i32 = 0;
     let mut ptr = input.as_ptr();
     let mut equations_processed = 0;
 
+    trace_phase!("solve");
+
     while equations_processed < NUM_EQUATIONS {
         // Extract digits for X_A
         let x_a_d1 = *ptr.add(12) as i32;
@@ -81,6 +85,8 @@ unsafe fn part2_scalar_impl(input: &[u8]) -> i64 {
     let mut ptr = input.as_ptr();
     let mut equations_processed = 0;
 
+    trace_phase!("solve");
+
     while equations_processed < NUM_EQUATIONS {
         // Extract digits for X_A
         let x_a_d1 = *ptr.add(12) as i64;
@@ -221,6 +227,37 @@ mod tests {
         assert_eq!(compute_i32(69, 27, 23, 71, 18641, 10279), 0);
     }
 
+    #[test]
+    fn test_compute_matches_math() {
+        use crate::math::solve_2x2_integer;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..1000 {
+            let (x_a, x_b, y_a, y_b) = (
+                rng.gen_range(10..100),
+                rng.gen_range(10..100),
+                rng.gen_range(10..100),
+                rng.gen_range(10..100),
+            );
+            if x_a * y_b == y_a * x_b {
+                continue;
+            }
+            // Prizes reachable with whole presses, like every machine in the inputs
+            let (a, b) = (
+                rng.gen_range(0..100000000000),
+                rng.gen_range(0..100000000000),
+            );
+            let (x, y) = (a * x_a + b * x_b, a * y_a + b * y_b);
+            assert_eq!(
+                solve_2x2_integer([[x_a, x_b], [y_a, y_b]], [x, y]),
+                Some([a, b])
+            );
+            assert_eq!(compute_i64(x_a, x_b, y_a, y_b, x, y), 3 * a + b);
+        }
+    }
+
     #[test]
     fn test_compare_part1_with_file() {
         let paths = [
@@ -1,7 +1,9 @@
-use std::cmp::Ordering;
+use crate::math::mod_inverse;
+use core::cmp::Ordering;
 
-#[aoc(day14, part1)]
+#[cfg_attr(feature = "std", aoc(day14, part1))]
 pub fn part1(input: &str) -> u32 {
+    trace_span!("day14::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
@@ -10,11 +12,11 @@ const HEIGHT: i32 = 103;
 const X_MID: i32 = 50;
 const Y_MID: i32 = 51;
 const MODULO_PRODUCT: i32 = WIDTH * HEIGHT; // 10403
-const X_FACTOR: i32 = HEIGHT * modular_inverse(HEIGHT, WIDTH);
-const Y_FACTOR: i32 = WIDTH * modular_inverse(WIDTH, HEIGHT);
+const X_FACTOR: i32 = HEIGHT * mod_inverse(HEIGHT as i64, WIDTH as i64).unwrap() as i32;
+const Y_FACTOR: i32 = WIDTH * mod_inverse(WIDTH as i64, HEIGHT as i64).unwrap() as i32;
 const VARIANCE_THRESHOLD: f32 = 540.0;
 
-const NUM_ROBOTS: usize = 500;
+pub(crate) const NUM_ROBOTS: usize = 500;
 const P1_TIMESTEPS: i32 = 100;
 
 unsafe fn part1_impl(input: &[u8]) -> u32 {
@@ -23,6 +25,7 @@ unsafe fn part1_impl(input: &[u8]) -> u32 {
     let mut q2 = 0u32;
     let mut q3 = 0u32;
     let mut q4 = 0u32;
+    trace_phase!("simulate");
     for _ in 0..NUM_ROBOTS {
         let (px, py, vx, vy) = parse_line_naive(&mut ptr);
 
@@ -40,8 +43,9 @@ unsafe fn part1_impl(input: &[u8]) -> u32 {
     q1 * q2 * q3 * q4
 }
 
-#[aoc(day14, part2)]
+#[cfg_attr(feature = "std", aoc(day14, part2))]
 pub fn part2(input: &str) -> i32 {
+    trace_span!("day14::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
@@ -81,6 +85,7 @@ macro_rules! compute_t {
 
 macro_rules! compute_next_var {
     ($func_name:ident, $data:ident, $dim:expr) => {
+        #[cfg(any(feature = "std", test))]
         #[inline(always)]
         #[allow(static_mut_refs)]
         #[allow(dead_code)]
@@ -127,11 +132,14 @@ compute_next_var!(compute_next_var_y, Y, HEIGHT);
 const SAMPLE_DIVISOR: usize = 5;
 const SAMPLE_ROBOTS: usize = NUM_ROBOTS / SAMPLE_DIVISOR;
 const SAMPLE_SIZE: f32 = SAMPLE_ROBOTS as f32;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut X: [(i32, i32); SAMPLE_ROBOTS] = [(0, 0); SAMPLE_ROBOTS];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut Y: [(i32, i32); SAMPLE_ROBOTS] = [(0, 0); SAMPLE_ROBOTS];
 
 #[allow(static_mut_refs)]
 unsafe fn part2_impl(input: &[u8]) -> i32 {
+    trace_phase!("parse");
     let mut ptr = input.as_ptr().add(2);
     // Take a reasonable sample of robots
     for i in 0..SAMPLE_ROBOTS {
@@ -139,12 +147,15 @@ unsafe fn part2_impl(input: &[u8]) -> i32 {
         *X.get_unchecked_mut(i) = (px, vx);
         *Y.get_unchecked_mut(i) = (py, vy);
     }
+    trace_phase!("search");
     let (t_x, _var_x) = compute_t_x();
     let (t_y, _var_y) = compute_t_y();
 
+    trace_phase!("reduce");
     crt(t_x, t_y) as i32
 }
 
+#[cfg(any(feature = "std", test))]
 #[allow(static_mut_refs)]
 #[allow(dead_code)]
 unsafe fn part2_analysis(input: &[u8], offset: usize) -> i32 {
@@ -166,6 +177,7 @@ unsafe fn part2_analysis(input: &[u8], offset: usize) -> i32 {
     crt(t_x, t_y) as i32
 }
 
+#[cfg(any(feature = "std", test))]
 #[allow(dead_code)]
 unsafe fn visualize(input: &[u8], t: i32) {
     let mut ptr = input.as_ptr().add(2);
@@ -181,19 +193,6 @@ unsafe fn visualize(input: &[u8], t: i32) {
     }
 }
 
-const fn modular_inverse(a: i32, b: i32) -> i32 {
-    let (mut old_r, mut r) = (a, b);
-    let (mut old_s, mut s) = (1, 0);
-
-    while r != 0 {
-        let quotient = old_r / r;
-        (old_r, r) = (r, old_r - quotient * r);
-        (old_s, s) = (s, old_s - quotient * s);
-    }
-
-    old_s.rem_euclid(b)
-}
-
 #[inline(always)]
 fn crt(x: i32, y: i32) -> i32 {
     let t1 = x * X_FACTOR;
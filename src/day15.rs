@@ -1,16 +1,17 @@
+use core::mem::MaybeUninit;
+use core::ptr;
 use memchr::{memchr, memchr_iter};
-use std::mem::MaybeUninit;
-use std::ptr;
 
-#[aoc(day15, part1)]
+#[cfg_attr(feature = "std", aoc(day15, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day15::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
 // Input constraints
-const GRID_LINES: usize = 50;
-const INSTRUCTION_LINES: usize = 20;
-const INSTRUCTIONS_PER_LINE: usize = 1000;
+pub(crate) const GRID_LINES: usize = 50;
+pub(crate) const INSTRUCTION_LINES: usize = 20;
+pub(crate) const INSTRUCTIONS_PER_LINE: usize = 1000;
 
 const LINE_LEN: usize = GRID_LINES + 1; // 50 characters + 1 newline
 const PADDING: usize = LINE_LEN;
@@ -39,6 +40,7 @@ const DIR_OFFSETS2: [isize; 256] = {
 };
 
 unsafe fn part1_impl(input: &[u8]) -> usize {
+    trace_phase!("parse");
     let mut grid_uninit: MaybeUninit<[u8; TOTAL_GRID_SIZE]> = MaybeUninit::uninit();
     let grid_ptr = grid_uninit.as_mut_ptr() as *mut u8;
 
@@ -62,6 +64,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
     let grid_mut_ptr = grid.as_mut_ptr();
     let mut instructions_ptr = input.as_ptr().add(GRID_LINES * LINE_LEN + 1);
 
+    trace_phase!("simulate");
     for _ in 0..INSTRUCTION_LINES {
         for _ in 0..INSTRUCTIONS_PER_LINE {
             let instr = *instructions_ptr;
@@ -93,6 +96,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
         instructions_ptr = instructions_ptr.add(1);
     }
 
+    trace_phase!("reduce");
     let mut gps_sum: usize = 0;
     for pos in memchr_iter(b'O', &grid[PADDING..PADDING + GRID_LINES * LINE_LEN]) {
         let row = pos / LINE_LEN;
@@ -103,30 +107,34 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
     gps_sum
 }
 
-#[aoc(day15, part2)]
+#[cfg_attr(feature = "std", aoc(day15, part2))]
 pub fn part2(input: &str) -> usize {
+    trace_span!("day15::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
+#[cfg(any(feature = "std", test))]
 #[allow(dead_code)]
 fn print_grid(grid: &[u8; TOTAL_GRID_SIZE]) {
     for line_start in (0..TOTAL_GRID_SIZE).step_by(LINE_LEN) {
         let line_end = line_start + LINE_LEN;
-        let line = unsafe { std::str::from_utf8_unchecked(&grid[line_start..line_end]) };
+        let line = unsafe { core::str::from_utf8_unchecked(&grid[line_start..line_end]) };
         println!("{}", line);
     }
 }
 
+#[cfg(any(feature = "std", test))]
 #[allow(dead_code)]
 fn print_stretched_grid(grid: &[u8; TOTAL_GRID_SIZE2]) {
     for line in grid.chunks_exact(LINE_LEN2) {
-        let line = unsafe { std::str::from_utf8_unchecked(line) };
+        let line = unsafe { core::str::from_utf8_unchecked(line) };
         println!("{}", line);
     }
 }
 
 #[allow(static_mut_refs)]
 unsafe fn part2_impl(input: &[u8]) -> usize {
+    trace_phase!("parse");
     let mut grid_input = &input[..GRID_LINES * LINE_LEN];
 
     let mut grid_uninit: MaybeUninit<[u8; TOTAL_GRID_SIZE2]> = MaybeUninit::uninit();
@@ -180,6 +188,7 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
     let grid = grid_uninit.assume_init_mut();
     let grid_mut_ptr = grid.as_mut_ptr();
     let mut instructions_ptr = input.as_ptr().add(GRID_LINES * LINE_LEN + 1);
+    trace_phase!("simulate");
     for _ in 0..INSTRUCTION_LINES {
         for _ in 0..INSTRUCTIONS_PER_LINE {
             let instr = *instructions_ptr;
@@ -198,6 +207,7 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
         instructions_ptr = instructions_ptr.add(1);
     }
 
+    trace_phase!("reduce");
     let mut gps_sum: usize = 0;
     let start_index = PADDING2; // start of actual map
     let end_index = PADDING2 + GRID_LINES * LINE_LEN2;
@@ -236,8 +246,11 @@ unsafe fn move_horizontal(grid_ptr: *mut u8, mut robot_pos: isize, offset: isize
     robot_pos
 }
 
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut VISITED: [u32; TOTAL_GRID_SIZE2] = [0; TOTAL_GRID_SIZE2];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u32 = 1;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut STACK: [usize; 512] = [0; 512];
 
 #[allow(static_mut_refs)]
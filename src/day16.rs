@@ -1,14 +1,16 @@
-#[aoc(day16, part1)]
+#[cfg_attr(feature = "std", aoc(day16, part1))]
 pub fn part1(input: &str) -> u32 {
+    trace_span!("day16::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
-#[aoc(day16, part2)]
+#[cfg_attr(feature = "std", aoc(day16, part2))]
 pub fn part2(input: &str) -> usize {
+    trace_span!("day16::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
-const GRID_SIZE: usize = 141;
+pub(crate) const GRID_SIZE: usize = 141;
 const LINE_LEN: usize = GRID_SIZE + 1;
 const START_POS: usize = (GRID_SIZE - 2) * LINE_LEN + 1;
 const START_POS_DIR: usize = combine_pos_dir(START_POS, 0);
@@ -33,15 +35,18 @@ const fn combine_pos_dir(pos: usize, dir: usize) -> usize {
 }
 
 // Only horizontals/verticals are checked for visited
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut VISITED_DIST: [(u32, u32); STATE_COUNT / 2] = [(0, 0); STATE_COUNT / 2];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut VALID_SPOT: [u32; GRID_SIZE * LINE_LEN] = [0; GRID_SIZE * LINE_LEN];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u32 = 0;
 
 #[allow(static_mut_refs)]
 unsafe fn part1_impl(input: &[u8]) -> u32 {
     TRUE = TRUE.wrapping_add(1);
     if TRUE == 0 {
-        std::ptr::write_bytes(VISITED_DIST.as_mut_ptr(), 0, STATE_COUNT);
+        core::ptr::write_bytes(VISITED_DIST.as_mut_ptr(), 0, STATE_COUNT);
         TRUE = 1;
     }
 
@@ -50,6 +55,7 @@ unsafe fn part1_impl(input: &[u8]) -> u32 {
     turn_queue_clear();
     forward_queue_push(0, START_POS, 0);
 
+    trace_phase!("search");
     loop {
         // 1.. -> 1001 -> 2.. -> 1002 -> 3.. -> 1003 -> 4.. -> 1004
         // Handle forwards first as they are the cheapest
@@ -130,6 +136,7 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
     let min_cost_found = part1_impl(input);
 
     // Phase 2 - trace back valid paths from the end pos
+    trace_phase!("backtrack");
     forward_queue_clear();
     turn_queue_clear();
     for i in 0..4 {
@@ -175,7 +182,7 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
     // for i in 0..GRID_SIZE {
     //     println!(
     //         "{}",
-    //         std::str::from_utf8_unchecked(&canvas[i * LINE_LEN..(i + 1) * LINE_LEN])
+    //         core::str::from_utf8_unchecked(&canvas[i * LINE_LEN..(i + 1) * LINE_LEN])
     //     );
     // }
 
@@ -224,17 +231,21 @@ unsafe fn expand_backward_turn(cost: u32, pos: usize, dir: usize) {
     }
 }
 
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut FORWARD_QUEUE: [QueueItem; STATE_COUNT] = [QueueItem {
     cost: 0,
     pos: 0,
     dir: 0,
 }; STATE_COUNT];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut FORWARD_QUEUE_SIZE: usize = 0;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TURN_QUEUE: [QueueItem; STATE_COUNT] = [QueueItem {
     cost: 0,
     pos: 0,
     dir: 0,
 }; STATE_COUNT];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TURN_QUEUE_SIZE: usize = 0;
 
 #[inline(always)]
@@ -1,32 +1,33 @@
-use std::hint::unreachable_unchecked;
-use std::ops::Mul;
-use std::simd::num::{SimdInt, SimdUint};
-use std::simd::{simd_swizzle, Simd};
-use std::str;
-
-#[aoc(day17, part1)]
-pub fn part1(input: &str) -> &'static str {
+use crate::answer::InlineStr;
+use core::hint::unreachable_unchecked;
+use core::ops::Mul;
+use core::simd::num::{SimdInt, SimdUint};
+use core::simd::{simd_swizzle, Simd};
+
+#[cfg_attr(feature = "std", aoc(day17, part1))]
+pub fn part1(input: &str) -> InlineStr<BUFFER_SIZE> {
+    trace_span!("day17::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
-#[aoc(day17, part2)]
+#[cfg_attr(feature = "std", aoc(day17, part2))]
 pub fn part2(input: &str) -> u64 {
+    trace_span!("day17::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
 const BUFFER_SIZE: usize = 64;
-static mut BUFFER: [u8; BUFFER_SIZE] = [0; BUFFER_SIZE];
 const MASK: [usize; 16] = [0, 2, 4, 6, 8, 10, 12, 14, 16, 18, 20, 22, 24, 26, 28, 30];
 const PROGRAM_OFFSET: usize = 59;
 const REG_A_OFFSET: usize = 12;
 const ASCII_ADJUST: u32 = 48 * (10000000 + 1000000 + 100000 + 10000 + 1000 + 100 + 10 + 1);
 
-#[allow(static_mut_refs)]
-unsafe fn part1_impl(input: &[u8]) -> &'static str {
+unsafe fn part1_impl(input: &[u8]) -> InlineStr<BUFFER_SIZE> {
+    trace_phase!("parse");
     let reg_a = parse_reg_a(&input[REG_A_OFFSET..]);
     let (x, y) = parse_xy_from_program(&input[PROGRAM_OFFSET..]);
-    let out_size = fast_compute(reg_a, x, y);
-    str::from_utf8_unchecked(&BUFFER[..out_size])
+    trace_phase!("run");
+    fast_compute(reg_a, x, y)
 }
 
 #[inline(always)]
@@ -76,17 +77,16 @@ unsafe fn quick_find_y(instructions: &[u8]) -> u64 {
 }
 
 #[inline(always)]
-#[allow(static_mut_refs)]
-unsafe fn fast_compute(mut reg_a: u32, x: u32, y: u32) -> usize {
-    let mut out_size = 0;
+unsafe fn fast_compute(mut reg_a: u32, x: u32, y: u32) -> InlineStr<BUFFER_SIZE> {
+    let mut out = InlineStr::new();
     while reg_a != 0 {
-        let out = fast_hash_u32(reg_a, x, y);
-        *BUFFER.get_unchecked_mut(out_size) = out as u8 + b'0';
-        *BUFFER.get_unchecked_mut(out_size + 1) = b',';
-        out_size += 2;
+        let digit = fast_hash_u32(reg_a, x, y);
+        out.push_unchecked(digit as u8 + b'0');
+        out.push_unchecked(b',');
         reg_a >>= 3;
     }
-    out_size - 1 // Get rid of the trailing comma
+    out.truncate(out.len() - 1); // Get rid of the trailing comma
+    out
 }
 
 #[inline(always)]
@@ -105,8 +105,10 @@ unsafe fn fast_hash_u64(reg_a: u64, x: u64, y: u64) -> u64 {
 
 #[inline(always)]
 unsafe fn part2_impl(input: &[u8]) -> u64 {
+    trace_phase!("parse");
     let instructions = parse_instructions(&input[PROGRAM_OFFSET..]);
     let (x, y) = parse_xy_u64(&instructions);
+    trace_phase!("search");
     Recursor {
         instructions: &instructions,
         x,
@@ -1,36 +1,42 @@
-use std::hint::unreachable_unchecked;
-use std::mem::MaybeUninit;
-use std::ptr;
-use std::str;
+use crate::answer::InlineStr;
+use core::mem::MaybeUninit;
+use core::ptr;
 
-#[aoc(day18, part1)]
+#[cfg_attr(feature = "std", aoc(day18, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day18::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
-#[aoc(day18, part2)]
-pub fn part2(_input: &str) -> &'static str {
+#[cfg_attr(feature = "std", aoc(day18, part2))]
+pub fn part2(_input: &str) -> InlineStr<5> {
+    trace_span!("day18::part2");
     unsafe { part2_impl(_input.as_bytes()) }
 }
 
-const GRID_SIZE: usize = 71;
+pub(crate) const GRID_SIZE: usize = 71;
 const LINE_LEN: usize = GRID_SIZE + 1;
 const PADDING: usize = LINE_LEN;
 const START_COORDS: usize = encode_coords(0, 1);
 const END_COORDS: usize = encode_coords(70, 71);
 const P1_BLOCKS: usize = 1024;
-const P2_START_FROM_BLOCKS: usize = 3200; // 3450 total, but we can risk it for the biscuit
+pub(crate) const P2_START_FROM_BLOCKS: usize = 3200; // 3450 total, but we can risk it for the biscuit
 
 const VISITED_SIZE: usize = GRID_SIZE * LINE_LEN + PADDING * 2;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut VISITED: [u16; VISITED_SIZE] = {
     let mut arr = [0; GRID_SIZE * LINE_LEN + PADDING * 2];
     reset_visited(&mut arr);
     arr
 };
 
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u16 = 0;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut NEXT: [([usize; VISITED_SIZE / 2], usize); 2] = [([0; VISITED_SIZE / 2], 0); 2];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut STACK: [usize; VISITED_SIZE] = [0; VISITED_SIZE];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut STACK_SIZE: usize = 0;
 
 const fn reset_visited(visited: &mut [u16; VISITED_SIZE]) {
@@ -55,6 +61,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
         reset_visited(&mut VISITED);
         TRUE = 1;
     }
+    trace_phase!("parse");
     let mut ptr = input.as_ptr();
     let mut i = 0;
     while i < P1_BLOCKS {
@@ -64,6 +71,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
         i += 1;
     }
 
+    trace_phase!("search");
     VISITED[START_COORDS] = TRUE;
     let next = NEXT.get_unchecked_mut(0);
     next.0[0] = START_COORDS;
@@ -121,13 +129,14 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
 }
 
 #[allow(static_mut_refs)]
-unsafe fn part2_impl(input: &[u8]) -> &'static str {
+unsafe fn part2_impl(input: &[u8]) -> InlineStr<5> {
     TRUE = TRUE.wrapping_add(2);
     if TRUE == 0 || TRUE == u16::MAX {
         ptr::write_bytes(VISITED.as_mut_ptr(), 0, VISITED_SIZE);
         reset_visited(&mut VISITED);
         TRUE = 2;
     }
+    trace_phase!("parse");
     let mut ptr = input.as_ptr();
     let mut blocks: MaybeUninit<[usize; P2_START_FROM_BLOCKS]> = MaybeUninit::uninit();
     let mut blocks_ptr = blocks.as_mut_ptr() as *mut MaybeUninit<usize>;
@@ -140,6 +149,7 @@ unsafe fn part2_impl(input: &[u8]) -> &'static str {
     }
     let blocks = blocks.assume_init();
 
+    trace_phase!("search");
     VISITED[START_COORDS] = TRUE;
     STACK[0] = START_COORDS;
     STACK_SIZE = 1;
@@ -157,7 +167,7 @@ unsafe fn part2_impl(input: &[u8]) -> &'static str {
             }
         }
     }
-    unreachable_unchecked();
+    unreachable!("The first {P2_START_FROM_BLOCKS} blocks do not cut off the exit");
 }
 
 #[inline(always)]
@@ -260,30 +270,24 @@ const fn encode_coords(x: usize, y: usize) -> usize {
     y * LINE_LEN + x
 }
 
-static mut BUFFER: [u8; 5] = [0; 5];
 #[inline(always)]
-#[allow(static_mut_refs)]
-unsafe fn decode_coords(coords: usize) -> &'static str {
-    let mut out_size = 0;
+unsafe fn decode_coords(coords: usize) -> InlineStr<5> {
+    let mut out = InlineStr::new();
     let x = coords % LINE_LEN;
     let y = coords / LINE_LEN - 1;
 
     if x >= 10 {
-        *BUFFER.get_unchecked_mut(out_size) = b'0' + (x / 10) as u8;
-        out_size += 1;
+        out.push_unchecked(b'0' + (x / 10) as u8);
     }
-    *BUFFER.get_unchecked_mut(out_size) = b'0' + (x % 10) as u8;
-    *BUFFER.get_unchecked_mut(out_size + 1) = b',';
-    out_size += 2;
+    out.push_unchecked(b'0' + (x % 10) as u8);
+    out.push_unchecked(b',');
 
     if y >= 10 {
-        *BUFFER.get_unchecked_mut(out_size) = b'0' + (y / 10) as u8;
-        out_size += 1;
+        out.push_unchecked(b'0' + (y / 10) as u8);
     }
-    *BUFFER.get_unchecked_mut(out_size) = b'0' + (y % 10) as u8;
-    out_size += 1;
+    out.push_unchecked(b'0' + (y % 10) as u8);
 
-    str::from_utf8_unchecked(&BUFFER[..out_size])
+    out
 }
 
 #[inline(always)]
@@ -306,6 +310,7 @@ fn down(coords: usize) -> usize {
     coords + LINE_LEN
 }
 
+#[cfg(any(feature = "std", test))]
 #[allow(static_mut_refs)]
 #[allow(dead_code)]
 unsafe fn visualize_grid() {
@@ -1,13 +1,15 @@
+use core::ptr;
 use memchr::memchr_iter;
-use std::ptr;
 
-#[aoc(day19, part1)]
+#[cfg_attr(feature = "std", aoc(day19, part1))]
 pub fn part1(input: &str) -> u64 {
+    trace_span!("day19::part1");
     unsafe { part1_impl(input.as_bytes()) as u64 }
 }
 
-#[aoc(day19, part2)]
-pub fn part2(input: &str) -> usize {
+#[cfg_attr(feature = "std", aoc(day19, part2))]
+pub fn part2(input: &str) -> u64 {
+    trace_span!("day19::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
@@ -21,15 +23,17 @@ const NODE_ID: [usize; 120] = {
     lut
 };
 
-const TOTAL_PATTERNS: usize = 447;
-const COUNTS_SIZE: usize = 61;
-const TRIE_SIZE: usize = 5000;
-const NODE_SIZE: usize = 6;
+pub(crate) const TOTAL_PATTERNS: usize = 447;
+pub(crate) const COUNTS_SIZE: usize = 61;
+pub(crate) const TRIE_SIZE: usize = 5000;
+pub(crate) const NODE_SIZE: usize = 6;
 // Every node takes 6 slots: i=0: 1 if terminal, 0 otherwise; i=(1..=5) - next nodes for wubrg.
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRIE: [usize; TRIE_SIZE] = [0; TRIE_SIZE];
 const IS_TERMINAL: usize = 1;
 
 const MAX_STACK_SIZE: usize = 128;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut STACK: [usize; MAX_STACK_SIZE] = [0; MAX_STACK_SIZE];
 
 #[inline(always)]
@@ -69,9 +73,12 @@ unsafe fn parse_pattern<const END_CHAR: u8>(ptr: *mut *const u8, next_empty_spac
 
 #[allow(static_mut_refs)]
 unsafe fn part1_impl(input: &[u8]) -> usize {
+    trace_phase!("build tables");
     let mut ptr = input.as_ptr();
     parse_trie(&mut ptr);
 
+    trace_phase!("search");
+
     let designs_start = ptr.offset_from(input.as_ptr()) as usize;
     let designs = &input[designs_start..];
     let mut possible_count = 0;
@@ -126,14 +133,17 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
 }
 
 #[allow(static_mut_refs)]
-unsafe fn part2_impl(input: &[u8]) -> usize {
+unsafe fn part2_impl(input: &[u8]) -> u64 {
+    trace_phase!("build tables");
     let mut ptr = input.as_ptr();
     parse_trie(&mut ptr);
 
+    trace_phase!("search");
+
     let designs_start = ptr.offset_from(input.as_ptr()) as usize;
     let designs = &input[designs_start..];
 
-    let mut counts = [0; COUNTS_SIZE];
+    let mut counts = [0u64; COUNTS_SIZE];
     let counts_reset_ptr = counts.as_mut_ptr().add(1);
     let mut total = 0;
 
@@ -161,7 +171,8 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
                     }
 
                     next += 1; // Expand
-                    *counts.get_unchecked_mut(next) += *TRIE.get_unchecked(offset) * start_count;
+                    *counts.get_unchecked_mut(next) +=
+                        *TRIE.get_unchecked(offset) as u64 * start_count;
                 }
             }
         }
@@ -1,4 +1,6 @@
-#[aoc(day2, part1, naive)]
+use alloc::vec::Vec;
+
+#[cfg_attr(feature = "std", aoc(day2, part1, naive))]
 pub fn part1_naive(input: &str) -> usize {
     input
         .lines()
@@ -19,7 +21,7 @@ pub fn part1_naive(input: &str) -> usize {
         .count() as usize
 }
 
-#[aoc(day2, part2, naive)]
+#[cfg_attr(feature = "std", aoc(day2, part2, naive))]
 pub fn part2_naive(input: &str) -> usize {
     input
         .lines()
@@ -48,8 +50,10 @@ pub fn part2_naive(input: &str) -> usize {
 }
 
 /// Assumptions: all numbers between 1 and 99, up to 10 numbers per line
-#[aoc(day2, part1)]
+#[cfg_attr(feature = "std", aoc(day2, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day2::part1");
+    trace_phase!("scan");
     let mut bytes = input.as_bytes();
     let mut safe_count = 0;
 
@@ -134,54 +138,64 @@ pub fn part1(input: &str) -> usize {
     safe_count
 }
 
-#[aoc(day2, part2)]
+#[cfg_attr(feature = "std", aoc(day2, part2))]
 pub fn part2(input: &str) -> usize {
+    trace_span!("day2::part2");
+    trace_phase!("scan");
     let mut safe_count = 0;
     let mut input_bytes = input.as_bytes();
 
-    let mut numbers = [0i32; 10];
+    let mut numbers = [0i32; MAX_LEVELS];
 
     while input_bytes.len() > 0 {
-        // New line
-        let mut length = 0;
+        let length = parse_report(&mut input_bytes, &mut numbers);
+        if length == 0 {
+            continue;
+        }
+        if is_safe_with_dampener(&numbers[..length]) {
+            safe_count += 1;
+        }
+    }
 
-        while input_bytes.len() > 0 {
-            let first_digit = input_bytes[0];
-            input_bytes = &input_bytes[1..];
+    safe_count
+}
 
-            if first_digit == b'\n' {
-                break;
-            }
+const MAX_LEVELS: usize = 10;
 
-            let number = if input_bytes.len() > 0 {
-                let second_digit = input_bytes[0];
-                if second_digit != b' ' && second_digit != b'\n' {
-                    input_bytes = &input_bytes[1..];
-                    (first_digit as i32) * 10 + (second_digit as i32) - 528
-                } else {
-                    (first_digit - b'0') as i32
-                }
-            } else {
-                (first_digit - b'0') as i32
-            };
+/// Parses the next line into `levels` and returns the number of levels on it.
+#[inline(always)]
+fn parse_report(input_bytes: &mut &[u8], levels: &mut [i32; MAX_LEVELS]) -> usize {
+    let mut length = 0;
 
-            numbers[length] = number;
-            length += 1;
+    while !input_bytes.is_empty() {
+        let first_digit = input_bytes[0];
+        *input_bytes = &input_bytes[1..];
 
-            if input_bytes.len() > 0 && input_bytes[0] == b' ' {
-                input_bytes = &input_bytes[1..];
-            }
+        if first_digit == b'\n' {
+            break;
         }
 
-        if length == 0 {
-            continue;
-        }
-        if is_safe_with_dampener(&numbers[..length]) {
-            safe_count += 1;
+        let number = if !input_bytes.is_empty() {
+            let second_digit = input_bytes[0];
+            if second_digit != b' ' && second_digit != b'\n' {
+                *input_bytes = &input_bytes[1..];
+                (first_digit as i32) * 10 + (second_digit as i32) - 528
+            } else {
+                (first_digit - b'0') as i32
+            }
+        } else {
+            (first_digit - b'0') as i32
+        };
+
+        levels[length] = number;
+        length += 1;
+
+        if !input_bytes.is_empty() && input_bytes[0] == b' ' {
+            *input_bytes = &input_bytes[1..];
         }
     }
 
-    safe_count
+    length
 }
 
 #[inline(always)]
@@ -191,28 +205,37 @@ fn is_safe_with_dampener(levels: &[i32]) -> bool {
 
 #[inline(always)]
 fn can_be_made_monotonic<const MIN_DIFF: i32, const MAX_DIFF: i32>(levels: &[i32]) -> bool {
-    let mut already_violated = false;
+    dampener_removal::<MIN_DIFF, MAX_DIFF>(levels).is_some()
+}
+
+/// `None` if no single removal makes `levels` safe in this direction,
+/// otherwise the index of the removed level (`Some(None)` if already safe).
+#[inline(always)]
+fn dampener_removal<const MIN_DIFF: i32, const MAX_DIFF: i32>(
+    levels: &[i32],
+) -> Option<Option<usize>> {
+    let mut removed = None;
     let mut i = 1;
     let mut diff = levels[1] - levels[0];
 
     loop {
         if diff < MIN_DIFF || diff > MAX_DIFF {
-            if already_violated {
-                return false;
+            if removed.is_some() {
+                return None;
             }
-            already_violated = true;
 
             // Decide whether to remove levels[i] or levels[i - 1]
             if i + 1 < levels.len() {
                 let new_diff = levels[i + 1] - levels[i - 1];
                 if new_diff >= MIN_DIFF && new_diff <= MAX_DIFF {
+                    removed = Some(i);
                     diff = new_diff; // Set up diff now with skipped levels[i]
                     i += 1;
                     continue;
                 }
             } else {
                 // We can always remove the final element
-                return true;
+                return Some(Some(i));
             }
 
             if i >= 2 {
@@ -220,19 +243,224 @@ fn can_be_made_monotonic<const MIN_DIFF: i32, const MAX_DIFF: i32>(levels: &[i32
                 if new_diff < MIN_DIFF || new_diff > MAX_DIFF {
                     // This was the last chance to make it right.
                     // Removing levels[i] or levels[i - 1] won't help to fix the violation.
-                    return false;
+                    return None;
                 } // Else we can remove levels[i - 1] which won't affect the next iteration
             } // Else we can always remove the first element without any further effect
+            removed = Some(i - 1);
         }
 
         i += 1;
         if i == levels.len() {
-            return true;
+            return Some(removed);
         }
         diff = levels[i] - levels[i - 1];
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reason {
+    /// The step goes against the direction set by the first step.
+    DirectionChange,
+    /// The step is not between 1 and 3 levels.
+    StepOutOfRange,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub reason: Reason,
+    /// Index of the level where the violating step ends.
+    pub index: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReportDiagnosis {
+    /// First violation, `None` if the report is safe.
+    pub violation: Option<Violation>,
+    /// Index of the level the dampener removes, `None` if the report is safe or can't be fixed.
+    pub removal: Option<usize>,
+}
+
+impl ReportDiagnosis {
+    pub fn is_safe(&self) -> bool {
+        self.violation.is_none()
+    }
+
+    pub fn is_safe_with_dampener(&self) -> bool {
+        self.violation.is_none() || self.removal.is_some()
+    }
+}
+
+/// Diagnoses every report, in input order. Same assumptions as `part1` and `part2`.
+/// Reports with a single level are safe.
+pub fn diagnose(input: &str) -> Vec<ReportDiagnosis> {
+    let mut input_bytes = input.as_bytes();
+    let mut numbers = [0i32; MAX_LEVELS];
+    let mut diagnoses = Vec::new();
+
+    while !input_bytes.is_empty() {
+        let length = parse_report(&mut input_bytes, &mut numbers);
+        if length == 0 {
+            continue;
+        }
+        diagnoses.push(diagnose_report(&numbers[..length]));
+    }
+
+    diagnoses
+}
+
+fn diagnose_report(levels: &[i32]) -> ReportDiagnosis {
+    let violation = first_violation(levels);
+    let removal = match violation {
+        None => None,
+        Some(_) => dampener_removal::<1, 3>(levels)
+            .or_else(|| dampener_removal::<-3, -1>(levels))
+            .flatten(),
+    };
+    ReportDiagnosis { violation, removal }
+}
+
+fn first_violation(levels: &[i32]) -> Option<Violation> {
+    let mut is_increasing = None;
+    for i in 1..levels.len() {
+        let diff = levels[i] - levels[i - 1];
+        let reason = match is_increasing {
+            _ if diff == 0 => Reason::StepOutOfRange,
+            Some(incr) if incr != (diff > 0) => Reason::DirectionChange,
+            _ if diff.abs() > 3 => Reason::StepOutOfRange,
+            _ => {
+                is_increasing = Some(diff > 0);
+                continue;
+            }
+        };
+        return Some(Violation { reason, index: i });
+    }
+    None
+}
+
+/// Safety check with runtime bounds: levels must move in one direction by `min_step..=max_step`
+/// per step, and up to `max_removals` levels may be removed. Works on any report length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Dampener {
+    pub max_removals: usize,
+    pub min_step: u32,
+    pub max_step: u32,
+}
+
+impl Default for Dampener {
+    /// The puzzle's Problem Dampener.
+    fn default() -> Self {
+        Self::new(1, 1, 3)
+    }
+}
+
+impl Dampener {
+    pub fn new(max_removals: usize, min_step: u32, max_step: u32) -> Self {
+        Self {
+            max_removals,
+            min_step,
+            max_step,
+        }
+    }
+
+    /// Number of safe reports in the input, one report of space separated levels per line.
+    pub fn count_safe(&self, input: &str) -> usize {
+        let mut levels = Vec::new();
+        input
+            .lines()
+            .filter(|line| {
+                parse_levels(line, &mut levels);
+                !levels.is_empty() && self.is_safe(&levels)
+            })
+            .count()
+    }
+
+    pub fn is_safe(&self, levels: &[u32]) -> bool {
+        self.min_removals(levels).is_some()
+    }
+
+    /// The fewest removals that make `levels` safe, `None` if more than `max_removals` are needed.
+    ///
+    /// O(n·k) for n levels and k = `max_removals`: the cheapest way to keep level `i` only
+    /// depends on the k + 1 levels before it, as a longer gap would remove too many levels.
+    pub fn min_removals(&self, levels: &[u32]) -> Option<usize> {
+        if levels.is_empty() {
+            return Some(0);
+        }
+        let increasing = self.min_removals_in_direction(levels, |prev, next| next - prev);
+        let decreasing = self.min_removals_in_direction(levels, |prev, next| prev - next);
+        Some(increasing.min(decreasing)).filter(|&removals| removals <= self.max_removals)
+    }
+
+    fn min_removals_in_direction(&self, levels: &[u32], step: impl Fn(i64, i64) -> i64) -> usize {
+        let (min_step, max_step) = (self.min_step as i64, self.max_step as i64);
+        let k = self.max_removals;
+        let n = levels.len();
+
+        // cost[i]: fewest removals among levels[..i] for a safe prefix ending at kept level i
+        let mut cost = Vec::with_capacity(n);
+        let mut best = usize::MAX;
+        for i in 0..n {
+            // Removing everything before level i is always an option
+            let mut cheapest = i;
+            for prev in i.saturating_sub(k.saturating_add(1))..i {
+                let gap = i - prev - 1;
+                let diff = step(levels[prev] as i64, levels[i] as i64);
+                if cost[prev] + gap < cheapest && diff >= min_step && diff <= max_step {
+                    cheapest = cost[prev] + gap;
+                }
+            }
+            cost.push(cheapest);
+            // Everything after level i is removed
+            best = best.min(cheapest + n - 1 - i);
+        }
+        best
+    }
+
+    /// Tries every way to remove up to `max_removals` levels, for testing `is_safe` with small k.
+    pub fn is_safe_naive(&self, levels: &[u32]) -> bool {
+        let is_safe_in_direction = |levels: &[u32], increasing: bool| {
+            levels.windows(2).all(|w| {
+                let diff = if increasing {
+                    w[1] as i64 - w[0] as i64
+                } else {
+                    w[0] as i64 - w[1] as i64
+                };
+                diff >= self.min_step as i64 && diff <= self.max_step as i64
+            })
+        };
+        if is_safe_in_direction(levels, true) || is_safe_in_direction(levels, false) {
+            return true;
+        }
+        if self.max_removals == 0 {
+            return false;
+        }
+
+        let fewer = Self {
+            max_removals: self.max_removals - 1,
+            ..*self
+        };
+        (0..levels.len()).any(|i| {
+            let mut removed = levels.to_vec();
+            removed.remove(i);
+            fewer.is_safe_naive(&removed)
+        })
+    }
+}
+
+/// Parses a line of space separated levels into `levels`, clearing it first.
+pub fn parse_levels(line: &str, levels: &mut Vec<u32>) {
+    levels.clear();
+    let mut number = None;
+    for &byte in line.as_bytes() {
+        if byte.is_ascii_digit() {
+            number = Some(number.unwrap_or(0) * 10 + (byte - b'0') as u32);
+        } else if let Some(n) = number.take() {
+            levels.push(n);
+        }
+    }
+    levels.extend(number);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,6 +544,136 @@ mod tests {
         assert_eq!(part1("72 73 73 74 75"), 0);
     }
 
+    #[test]
+    fn test_diagnose() {
+        let step = |index| Violation {
+            reason: Reason::StepOutOfRange,
+            index,
+        };
+        let direction = |index| Violation {
+            reason: Reason::DirectionChange,
+            index,
+        };
+        let diagnoses = diagnose(INPUT);
+        let expected = [
+            (None, None),
+            (Some(step(2)), None),
+            (Some(step(3)), None),
+            (Some(direction(2)), Some(2)),
+            (Some(step(3)), Some(3)),
+            (None, None),
+        ];
+        assert_eq!(diagnoses.len(), expected.len());
+        for (diagnosis, (violation, removal)) in diagnoses.iter().zip(expected) {
+            assert_eq!(diagnosis.violation, violation);
+            assert_eq!(diagnosis.removal, removal);
+        }
+
+        // The first step sets the direction, the dampener may pick the other one
+        assert_eq!(
+            diagnose("5 6 4 3 2 1"),
+            vec![ReportDiagnosis {
+                violation: Some(direction(2)),
+                removal: Some(1),
+            }]
+        );
+    }
+
+    /// One report line with `levels` separated by spaces.
+    fn report_line<T: ToString>(levels: &[T]) -> String {
+        levels
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_diagnose_matches_naive() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..5000 {
+            let length = rng.gen_range(2..=MAX_LEVELS);
+            let mut levels = vec![rng.gen_range(1..=99)];
+            for _ in 1..length {
+                let prev: i32 = *levels.last().unwrap();
+                levels.push((prev + rng.gen_range(-4..=4)).clamp(1, 99));
+            }
+            let line = report_line(&levels);
+
+            let diagnosis = diagnose(&line)[0];
+            assert_eq!(diagnosis.is_safe(), part1_naive(&line) == 1, "{line}");
+            assert_eq!(
+                diagnosis.is_safe_with_dampener(),
+                part2_naive(&line) == 1,
+                "{line}"
+            );
+            if let Some(removal) = diagnosis.removal {
+                let mut dampened = levels.clone();
+                dampened.remove(removal);
+                let dampened = report_line(&dampened);
+                assert_eq!(part1_naive(&dampened), 1, "{line} without {removal}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dampener_example() {
+        assert_eq!(Dampener::new(0, 1, 3).count_safe(INPUT), 2);
+        assert_eq!(Dampener::default().count_safe(INPUT), 4);
+        assert_eq!(Dampener::new(2, 1, 3).count_safe(INPUT), 6);
+        assert_eq!(Dampener::new(0, 1, 5).count_safe(INPUT), 4);
+        assert_eq!(
+            Dampener::new(1, 1000, 2000).min_removals(&[0, 1500, 4_000_000_000, 3000]),
+            Some(1)
+        );
+        // `usize::MAX` means no limit
+        let unlimited = Dampener::new(usize::MAX, 1, 3);
+        assert_eq!(unlimited.count_safe(INPUT), 6);
+        assert_eq!(unlimited.min_removals(&[1, 2, 7, 8, 9]), Some(2));
+    }
+
+    #[test]
+    fn test_dampener_matches_naive() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..2000 {
+            let dampener = Dampener::new(
+                rng.gen_range(0..=3),
+                rng.gen_range(0..=2),
+                rng.gen_range(2..=4),
+            );
+            let length = rng.gen_range(0..=12);
+            let mut levels = vec![rng.gen_range(10..=20)];
+            for _ in 1..length {
+                let prev: u32 = *levels.last().unwrap();
+                levels.push(prev.saturating_add_signed(rng.gen_range(-5..=5)));
+            }
+            assert_eq!(
+                dampener.is_safe(&levels),
+                dampener.is_safe_naive(&levels),
+                "{dampener:?} {levels:?}"
+            );
+        }
+
+        // The fast solver agrees with the default dampener
+        for _ in 0..2000 {
+            let levels: Vec<u32> = (0..rng.gen_range(2..=MAX_LEVELS))
+                .map(|_| rng.gen_range(1..=99))
+                .collect();
+            let line = report_line(&levels);
+            assert_eq!(
+                Dampener::default().count_safe(&line),
+                part2(&line),
+                "{line}"
+            );
+        }
+    }
+
     #[test]
     fn test_compare_part1_with_file() {
         let module_dir = Path::new(file!()).parent().unwrap();
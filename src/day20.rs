@@ -1,21 +1,26 @@
+use core::ptr;
 use memchr::{memchr, memchr2};
-use std::ptr;
 
-const GRID_SIZE: usize = 141;
+pub(crate) const GRID_SIZE: usize = 141;
 const LINE_LEN: usize = GRID_SIZE + 1;
 const VISITED_SIZE: usize = GRID_SIZE * LINE_LEN;
 // TRUE/FALSE => cost = value - TRUE
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut VISITED: [u32; VISITED_SIZE] = [0; VISITED_SIZE];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u32 = 0;
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut NEXT: [([usize; VISITED_SIZE / 2], usize); 2] = [([0; VISITED_SIZE / 2], 0); 2];
 
-#[aoc(day20, part1)]
+#[cfg_attr(feature = "std", aoc(day20, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day20::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
-#[aoc(day20, part2)]
+#[cfg_attr(feature = "std", aoc(day20, part2))]
 pub fn part2(input: &str) -> usize {
+    trace_span!("day20::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
@@ -26,6 +31,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
         ptr::write_bytes(VISITED.as_mut_ptr(), 0, VISITED_SIZE);
     }
 
+    trace_phase!("parse");
     let (start_pos, end_pos) = find_start_end(input);
     *VISITED.get_unchecked_mut(end_pos) = TRUE;
     let next = NEXT.get_unchecked_mut(0);
@@ -35,6 +41,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
     let mut cost = 0u32;
 
     // Phase 1: need to reach at least 101 cost before we can start saving anything
+    trace_phase!("search");
     while cost < 101 {
         let queue = NEXT.get_unchecked_mut((cost & 1) as usize);
         cost += 1;
@@ -51,6 +58,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
     }
 
     // Phase 2: continue exploring until we reach start, but also start checking for cheats
+    trace_phase!("count cheats");
     let mut cheats = 0;
     let mut cheat_threshold = TRUE; // Nodes that are less than this value are valid cheats
     loop {
@@ -138,6 +146,7 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
         ptr::write_bytes(VISITED.as_mut_ptr(), 0, VISITED_SIZE);
     }
 
+    trace_phase!("parse");
     let (start_pos, end_pos) = find_start_end(input);
     *VISITED.get_unchecked_mut(end_pos) = TRUE;
     let next = NEXT.get_unchecked_mut(0);
@@ -147,6 +156,7 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
     let mut cost = 0u32;
 
     // Phase 1: need to reach at least 101 cost before we can start saving anything
+    trace_phase!("search");
     while cost < 101 {
         let queue = NEXT.get_unchecked_mut((cost & 1) as usize);
         cost += 1;
@@ -163,6 +173,7 @@ unsafe fn part2_impl(input: &[u8]) -> usize {
     }
 
     // Phase 2: continue exploring until we reach start, but also start checking for cheats
+    trace_phase!("count cheats");
     let mut cheats = 0;
     let mut cheat_threshold = TRUE + cost - 99; // Distances that are less than this value are valid cheats
     loop {
@@ -1,13 +1,16 @@
-use std::ptr;
-use std::simd::num::SimdUint;
-use std::simd::{simd_swizzle, Simd};
+use core::ptr;
+use core::simd::num::SimdUint;
+use core::simd::{simd_swizzle, Simd};
 
 include!("luts/day21_luts.rs");
 
-#[aoc(day21, part1)]
+#[cfg_attr(feature = "std", aoc(day21, part1))]
 pub fn part1(input: &str) -> u32 {
+    trace_span!("day21::part1");
     unsafe {
+        trace_phase!("parse");
         let (first, second, third, fourth, fifth) = parse_scalar(input.as_bytes());
+        trace_phase!("lookup");
         *P1.get_unchecked(first)
             + *P1.get_unchecked(second)
             + *P1.get_unchecked(third)
@@ -16,10 +19,13 @@ pub fn part1(input: &str) -> u32 {
     }
 }
 
-#[aoc(day21, part2)]
+#[cfg_attr(feature = "std", aoc(day21, part2))]
 pub fn part2(input: &str) -> u64 {
+    trace_span!("day21::part2");
     unsafe {
+        trace_phase!("parse");
         let (first, second, third, fourth, fifth) = parse_scalar(input.as_bytes());
+        trace_phase!("lookup");
         *P2.get_unchecked(first)
             + *P2.get_unchecked(second)
             + *P2.get_unchecked(third)
@@ -1,18 +1,23 @@
-use std::hint::unreachable_unchecked;
-use std::mem::transmute;
-use std::ptr;
+use core::hint::unreachable_unchecked;
+#[cfg(not(feature = "lean"))]
+use core::mem::transmute;
+use core::ptr;
+#[cfg(feature = "lean")]
+use core::simd::{num::SimdUint, Simd};
 
-#[aoc(day22, part1)]
-pub fn part1(input: &str) -> usize {
+#[cfg_attr(feature = "std", aoc(day22, part1))]
+pub fn part1(input: &str) -> u64 {
+    trace_span!("day22::part1");
     unsafe { part1_impl(input) }
 }
 
-#[aoc(day22, part2)]
+#[cfg_attr(feature = "std", aoc(day22, part2))]
 pub fn part2(input: &str) -> u16 {
+    trace_span!("day22::part2");
     unsafe { part2_impl(input) }
 }
 
-const MODULO: u32 = 1 << 24;
+pub(crate) const MODULO: u32 = 1 << 24;
 const NUM_CHANGES: usize = 2000;
 const SEQ_LEN: usize = 4;
 const BASE: usize = 19;
@@ -20,6 +25,7 @@ const BASE_POW_2: usize = BASE * BASE;
 const BASE_POW_3: usize = BASE * BASE * BASE;
 const TOTAL_SEQUENCES: usize = BASE * BASE * BASE * BASE;
 
+#[cfg(not(feature = "lean"))]
 static P1: [u32; MODULO as usize] = unsafe { transmute(*include_bytes!("luts/day22.bin")) };
 
 #[inline(always)]
@@ -36,8 +42,10 @@ fn sequence_to_idx(a: usize, b: usize, c: usize, d: usize) -> usize {
     a * BASE_POW_3 + b * BASE_POW_2 + c * BASE + d
 }
 
+#[cfg(not(feature = "lean"))]
 #[inline(always)]
-unsafe fn part1_impl(input: &str) -> usize {
+unsafe fn part1_impl(input: &str) -> u64 {
+    trace_phase!("lookup");
     let mut total_sum = 0;
 
     let mut ptr = input.as_ptr();
@@ -58,13 +66,59 @@ unsafe fn part1_impl(input: &str) -> usize {
         }
         ptr = ptr.add(1); // \n
 
-        total_sum += *P1.get_unchecked(num) as usize;
+        total_sum += *P1.get_unchecked(num) as u64;
     }
 
     total_sum
 }
 
+#[cfg(feature = "lean")]
+const LANES: usize = 8;
+
+/// Lean builds have no `P1`, so secrets are evolved `LANES` at a time instead.
+#[cfg(feature = "lean")]
+#[inline(always)]
+unsafe fn part1_impl(input: &str) -> u64 {
+    trace_phase!("simulate");
+    let mut total_sum = 0;
+    let mut secrets = [0u32; LANES];
+    let mut n = 0;
+
+    for line in input.as_bytes().split(|&b| b == b'\n') {
+        if line.is_empty() {
+            continue;
+        }
+        *secrets.get_unchecked_mut(n) = line.iter().fold(0, |num, &b| num * 10 + (b - b'0') as u32);
+        n += 1;
+        if n == LANES {
+            total_sum += evolve_secrets(Simd::from_array(secrets)).reduce_sum() as u64;
+            n = 0;
+        }
+    }
+    if n > 0 {
+        // Zero is a fixed point, so unused lanes add nothing
+        secrets[n..].fill(0);
+        total_sum += evolve_secrets(Simd::from_array(secrets)).reduce_sum() as u64;
+    }
+
+    total_sum
+}
+
+#[cfg(feature = "lean")]
+#[inline(always)]
+fn evolve_secrets(mut secrets: Simd<u32, LANES>) -> Simd<u32, LANES> {
+    let mask = Simd::splat(MODULO - 1);
+    for _ in 0..NUM_CHANGES {
+        secrets ^= (secrets << 6) & mask;
+        secrets ^= secrets >> 5;
+        secrets ^= (secrets << 11) & mask;
+    }
+    secrets
+}
+
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut SEEN: [u16; TOTAL_SEQUENCES] = [0; TOTAL_SEQUENCES];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u16 = 0;
 
 #[inline(always)]
@@ -76,6 +130,7 @@ unsafe fn part2_impl(input: &str) -> u16 {
         TRUE = 0;
     }
 
+    trace_phase!("search");
     let mut ptr = input.as_ptr();
     let end = ptr.add(input.len());
 
@@ -142,6 +197,7 @@ unsafe fn part2_impl(input: &str) -> u16 {
         }
     }
 
+    trace_phase!("reduce");
     *sum_sequences.iter().max().unwrap()
 }
 
@@ -153,6 +209,12 @@ mod tests {
 
     const DAY: u8 = 22;
 
+    #[test]
+    #[cfg(feature = "lean")]
+    fn test_part1_example() {
+        assert_eq!(part1("1\n10\n100\n2024\n"), 37327623);
+    }
+
     #[test]
     fn test_compare_part1_with_file() {
         let paths = [
@@ -1,5 +1,9 @@
-#[aoc(day23, part1)]
+use crate::answer::InlineStr;
+use alloc::vec::Vec;
+
+#[cfg_attr(feature = "std", aoc(day23, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day23::part1");
     unsafe { part1_impl(input.as_bytes()) }
 }
 
@@ -31,6 +35,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
         (b & m) != 0
     }
 
+    trace_phase!("parse");
     let mut adj = [0u8; 456_976 / 8];
     let mut used = [0u8; 676 / 8 + 1];
     let mut p = input.as_ptr();
@@ -50,6 +55,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
             p = p.add(1);
         }
     }
+    trace_phase!("build tables");
     let mut nodes = [0u16; 676];
     let mut count = 0;
     for i in 0..676 {
@@ -58,6 +64,7 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
             count += 1;
         }
     }
+    trace_phase!("search");
     let mut result = 0;
     for i in 0..count {
         let ni = nodes[i];
@@ -79,12 +86,14 @@ unsafe fn part1_impl(input: &[u8]) -> usize {
     result
 }
 
-#[aoc(day23, part2)]
-pub fn part2(input: &str) -> &'static str {
+#[cfg_attr(feature = "std", aoc(day23, part2))]
+pub fn part2(input: &str) -> InlineStr<ANSWER_SIZE> {
+    trace_span!("day23::part2");
     unsafe { part2_impl(input.as_bytes()) }
 }
 
-static mut BUFFER: [u8; 1024] = [0; 1024];
+/// Room for a clique of 85 computers.
+const ANSWER_SIZE: usize = 256;
 
 type BitSet = [u8; 85];
 
@@ -95,7 +104,8 @@ unsafe fn adjacency_set<'a>(adjacency_sets: *mut u8, i: u16) -> &'a mut BitSet {
 }
 
 #[allow(static_mut_refs)]
-unsafe fn part2_impl(input: &[u8]) -> &'static str {
+unsafe fn part2_impl(input: &[u8]) -> InlineStr<ANSWER_SIZE> {
+    trace_phase!("parse");
     let mut adj = [0u8; 456_976 / 8]; // 676*676 bits = 456,976 bits => /8 bytes
     let mut used = [0u8; 676 / 8 + 1];
     let mut p = input.as_ptr();
@@ -120,6 +130,8 @@ unsafe fn part2_impl(input: &[u8]) -> &'static str {
         }
     }
 
+    trace_phase!("build tables");
+    #[cfg_attr(feature = "parallel", thread_local)]
     static mut ADJ_SETS_BUF: [u8; 676 * 85] = [0; 676 * 85];
     ADJ_SETS_BUF.fill(0);
 
@@ -143,6 +155,7 @@ unsafe fn part2_impl(input: &[u8]) -> &'static str {
         }
     }
 
+    trace_phase!("search");
     let mut p = [0u8; 85];
     for ui in 0..used_count {
         let i = used_nodes[ui];
@@ -162,21 +175,20 @@ unsafe fn part2_impl(input: &[u8]) -> &'static str {
         &mut best_clique,
     );
 
+    trace_phase!("format");
     best_clique.sort_unstable();
 
-    let mut idx = 0;
+    let mut out = InlineStr::new();
     for (i, node_id) in best_clique.iter().enumerate() {
         if i > 0 {
-            BUFFER[idx] = b',';
-            idx += 1;
+            out.push(b',');
         }
         let (c1, c2) = decode_node(*node_id);
-        BUFFER[idx] = c1;
-        BUFFER[idx + 1] = c2;
-        idx += 2;
+        out.push(c1);
+        out.push(c2);
     }
 
-    core::str::from_utf8_unchecked(&BUFFER[..idx])
+    out
 }
 
 unsafe fn bron_kerbosch_pivot(
@@ -1,5 +1,11 @@
+use alloc::collections::VecDeque;
+#[cfg(not(feature = "std"))]
+use alloc::collections::{BTreeMap as FxHashMap, BTreeSet as FxHashSet};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use rustc_hash::{FxHashMap, FxHashSet};
-use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Copy)]
 enum Op {
@@ -16,8 +22,10 @@ struct Gate {
     op: Op,
 }
 
-#[aoc(day24, part1)]
+#[cfg_attr(feature = "std", aoc(day24, part1))]
 pub fn part1(input: &str) -> u64 {
+    trace_span!("day24::part1");
+    trace_phase!("parse");
     let (initial_part, gates_part) = input.split_once("\n\n").unwrap();
 
     let mut wire_names = Vec::new();
@@ -67,6 +75,7 @@ pub fn part1(input: &str) -> u64 {
         gates.push(Gate { in1, in2, out, op });
     }
 
+    trace_phase!("build tables");
     let mut dependencies: Vec<Vec<usize>> = vec![Vec::new(); wire_names.len()];
     let mut unresolved_counts = vec![2; gates.len()];
     for (gate_idx, gate) in gates.iter().enumerate() {
@@ -90,6 +99,7 @@ pub fn part1(input: &str) -> u64 {
     }
     let mut gates_queue = VecDeque::new();
 
+    trace_phase!("simulate");
     loop {
         while let Some(wire_idx) = resolved_wires.pop_front() {
             for &gate_idx in &dependencies[wire_idx] {
@@ -122,6 +132,7 @@ pub fn part1(input: &str) -> u64 {
         }
     }
 
+    trace_phase!("reduce");
     let mut result = 0;
     for (idx, name) in wire_names.iter().enumerate() {
         if name.starts_with('z') {
@@ -135,8 +146,10 @@ pub fn part1(input: &str) -> u64 {
     result
 }
 
-#[aoc(day24, part2)]
+#[cfg_attr(feature = "std", aoc(day24, part2))]
 pub fn part2(input: &str) -> String {
+    trace_span!("day24::part2");
+    trace_phase!("parse");
     let (_, gates_part) = input.split_once("\n\n").unwrap();
     let mut output_set = FxHashSet::default();
 
@@ -153,6 +166,7 @@ pub fn part2(input: &str) -> String {
         output_set.insert((right, op));
     }
 
+    trace_phase!("search");
     let mut swapped = FxHashSet::default();
 
     for gate in &gates {
@@ -203,6 +217,7 @@ pub fn part2(input: &str) -> String {
         }
     }
 
+    trace_phase!("format");
     let mut swapped_list: Vec<String> = swapped.into_iter().collect();
     swapped_list.sort_unstable();
 
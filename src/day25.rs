@@ -1,5 +1,9 @@
-#[aoc(day25, part1)]
+use alloc::vec::Vec;
+
+#[cfg_attr(feature = "std", aoc(day25, part1))]
 pub fn part1(input: &str) -> u32 {
+    trace_span!("day25::part1");
+    trace_phase!("parse");
     let mut bytes = input.as_bytes();
     let mut locks = Vec::new();
     let mut keys = Vec::new();
@@ -19,14 +23,16 @@ pub fn part1(input: &str) -> u32 {
         bytes = if bytes.len() >= 43 { &bytes[43..] } else { &[] };
     }
 
+    trace_phase!("search");
     locks
         .iter()
         .map(|l| keys.iter().filter(|k| (l & *k) == 0).count() as u32)
         .sum()
 }
 
-#[aoc(day25, part2)]
+#[cfg_attr(feature = "std", aoc(day25, part2))]
 pub fn part2(_input: &str) -> u64 {
+    trace_span!("day25::part2");
     0
 }
 
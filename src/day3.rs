@@ -1,65 +1,75 @@
+use alloc::vec::Vec;
+use core::ops::Range;
 use memchr::memchr_iter;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
 
-#[aoc(day3, part1, naive)]
+#[cfg_attr(feature = "std", aoc(day3, part1, naive))]
 pub fn part1_naive(input: &str) -> u64 {
     part1(input)
 }
 
-#[aoc(day3, part2, naive)]
+#[cfg_attr(feature = "std", aoc(day3, part2, naive))]
 pub fn part2_naive(input: &str) -> u64 {
     part2(input)
 }
 
-#[aoc(day3, part1)]
+#[cfg_attr(feature = "std", aoc(day3, part1))]
 pub fn part1(input: &str) -> u64 {
+    trace_span!("day3::part1");
+    trace_phase!("scan");
+    let bytes = input.as_bytes();
     let mut sum: u64 = 0;
-    process_all_mul(input.as_bytes(), &mut sum);
+    process_all_mul(bytes, bytes.len(), &mut sum);
     sum
 }
 
-#[aoc(day3, part2)]
+#[cfg_attr(feature = "std", aoc(day3, part2))]
 pub fn part2(input: &str) -> u64 {
+    trace_span!("day3::part2");
+    trace_phase!("scan");
     let bytes = input.as_bytes();
-    let n = bytes.len();
     let mut sum: u64 = 0;
-    let mut pos: usize = 0;
     let mut enabled: bool = true;
+    process_conditional(bytes, bytes.len(), &mut enabled, &mut sum);
+    sum
+}
 
-    for d_pos in memchr_iter(b'd', bytes) {
-        if d_pos >= n {
-            break;
-        }
+/// Part 2 over the instructions that start before `end`, they may read past it.
+fn process_conditional(bytes: &[u8], end: usize, enabled: &mut bool, sum: &mut u64) {
+    let n = bytes.len();
+    let mut pos: usize = 0;
 
-        if enabled {
+    for d_pos in memchr_iter(b'd', &bytes[..end]) {
+        if *enabled {
             if d_pos + 7 <= n && &bytes[d_pos..d_pos + 7] == b"don't()" {
                 // Process all "mul(x,y)" before "don't()"
-                process_all_mul(&bytes[pos..d_pos], &mut sum);
+                process_all_mul(&bytes[pos..], d_pos - pos, sum);
 
                 pos = d_pos + 7; // "don't()"
-                enabled = false;
+                *enabled = false;
                 continue;
             }
         } else {
             if d_pos + 4 <= n && &bytes[d_pos..d_pos + 4] == b"do()" {
                 pos = d_pos + 4; // "do()"
-                enabled = true;
+                *enabled = true;
                 continue;
             }
         }
     }
 
     // Process leftovers
-    if enabled && pos < n {
-        process_all_mul(&bytes[pos..n], &mut sum);
+    if *enabled && pos < end {
+        process_all_mul(&bytes[pos..], end - pos, sum);
     }
-
-    sum
 }
 
-fn process_all_mul(bytes: &[u8], sum: &mut u64) {
+/// Sums the `mul(x,y)` that start before `end`, they may read past it.
+fn process_all_mul(bytes: &[u8], end: usize, sum: &mut u64) {
     let n = bytes.len();
 
-    for m_pos in memchr_iter(b'm', bytes) {
+    for m_pos in memchr_iter(b'm', &bytes[..end]) {
         if m_pos + 4 > n {
             continue;
         }
@@ -102,6 +112,285 @@ fn process_all_mul(bytes: &[u8], sum: &mut u64) {
     }
 }
 
+/// What a recognized instruction does.
+#[derive(Clone, Copy, Debug)]
+pub enum Effect {
+    /// Adds a value computed from the operands to the sum, while enabled.
+    Accumulate(fn(&[u64]) -> u64),
+    Enable,
+    Disable,
+}
+
+/// An instruction of the form `name(operand,operand,...)`.
+#[derive(Clone, Copy, Debug)]
+pub struct Instruction {
+    pub name: &'static str,
+    /// Up to `MAX_ARITY` operands.
+    pub arity: usize,
+    /// Operands are `min_digits..=max_digits` decimal digits.
+    pub min_digits: usize,
+    pub max_digits: usize,
+    pub effect: Effect,
+}
+
+pub const MAX_ARITY: usize = 8;
+
+pub const MUL: Instruction = Instruction {
+    name: "mul",
+    arity: 2,
+    min_digits: 1,
+    max_digits: 3,
+    effect: Effect::Accumulate(|operands| operands[0] * operands[1]),
+};
+pub const DO: Instruction = Instruction {
+    name: "do",
+    arity: 0,
+    min_digits: 0,
+    max_digits: 0,
+    effect: Effect::Enable,
+};
+pub const DONT: Instruction = Instruction {
+    name: "don't",
+    arity: 0,
+    min_digits: 0,
+    max_digits: 0,
+    effect: Effect::Disable,
+};
+
+/// The instruction sets of `part1` and `part2`.
+pub const PART1: &[Instruction] = &[MUL];
+pub const PART2: &[Instruction] = &[MUL, DO, DONT];
+
+/// A recognized instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// Index into the instruction set.
+    pub instruction: usize,
+    /// Byte range of the instruction in the input.
+    pub span: Range<usize>,
+    pub operands: Vec<u64>,
+    /// Whether the instruction was enabled, only `Accumulate` depends on it.
+    pub enabled: bool,
+}
+
+/// Every instruction of `instructions` found in the input, in order.
+pub fn scan(input: &str, instructions: &[Instruction]) -> Vec<Match> {
+    let mut matches = Vec::new();
+    run(
+        input.as_bytes(),
+        instructions,
+        |instruction, span, operands, enabled| {
+            matches.push(Match {
+                instruction,
+                span,
+                operands: operands.to_vec(),
+                enabled,
+            });
+        },
+    );
+    matches
+}
+
+/// The sum of all enabled `Accumulate` instructions.
+pub fn evaluate(input: &str, instructions: &[Instruction]) -> u64 {
+    let mut sum = 0;
+    run(
+        input.as_bytes(),
+        instructions,
+        |instruction, _, operands, enabled| {
+            if let (Effect::Accumulate(value), true) = (instructions[instruction].effect, enabled) {
+                sum += value(operands);
+            }
+        },
+    );
+    sum
+}
+
+/// Calls `found` for each instruction with its index, span, operands and the enabled state.
+/// Scanning resumes after the end of each match.
+fn run<F: FnMut(usize, Range<usize>, &[u64], bool)>(
+    bytes: &[u8],
+    instructions: &[Instruction],
+    mut found: F,
+) {
+    let mut starts = [false; 256];
+    for instruction in instructions {
+        assert!(
+            instruction.arity <= MAX_ARITY,
+            "{} has too many operands",
+            instruction.name
+        );
+        starts[instruction.name.as_bytes()[0] as usize] = true;
+    }
+
+    let mut operands = [0u64; MAX_ARITY];
+    let mut enabled = true;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if !starts[bytes[pos] as usize] {
+            pos += 1;
+            continue;
+        }
+        let matched = instructions
+            .iter()
+            .enumerate()
+            .find_map(|(index, instruction)| {
+                let end = parse_instruction(bytes, pos, instruction, &mut operands)?;
+                Some((index, end))
+            });
+        let Some((index, end)) = matched else {
+            pos += 1;
+            continue;
+        };
+
+        let instruction = &instructions[index];
+        found(index, pos..end, &operands[..instruction.arity], enabled);
+        match instruction.effect {
+            Effect::Accumulate(_) => {}
+            Effect::Enable => enabled = true,
+            Effect::Disable => enabled = false,
+        }
+        pos = end;
+    }
+}
+
+/// Parses `instruction` at `pos` into `operands`, returns the end of the match.
+fn parse_instruction(
+    bytes: &[u8],
+    pos: usize,
+    instruction: &Instruction,
+    operands: &mut [u64; MAX_ARITY],
+) -> Option<usize> {
+    let mut i = pos + instruction.name.len();
+    if bytes.get(pos..i)? != instruction.name.as_bytes() || *bytes.get(i)? != b'(' {
+        return None;
+    }
+    i += 1;
+
+    for (index, operand) in operands[..instruction.arity].iter_mut().enumerate() {
+        if index > 0 {
+            if *bytes.get(i)? != b',' {
+                return None;
+            }
+            i += 1;
+        }
+        // Like `parse_number`, takes at most `max_digits` even if more follow
+        let digits = bytes[i..]
+            .iter()
+            .take(instruction.max_digits)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits < instruction.min_digits.max(1) {
+            return None;
+        }
+        *operand = bytes[i..i + digits]
+            .iter()
+            .fold(0, |acc, &b| acc * 10 + (b - b'0') as u64);
+        i += digits;
+    }
+
+    (*bytes.get(i)? == b')').then_some(i + 1)
+}
+
+/// Longest instruction, `mul(123,456)`.
+#[cfg(feature = "std")]
+const MAX_INSTRUCTION_LEN: usize = 12;
+
+/// Streaming version of `part1` and `part2` for inputs that don't fit in memory.
+///
+/// Chunks are scanned as they arrive. The last `MAX_INSTRUCTION_LEN - 1` bytes of each chunk are
+/// kept back, as they may start an instruction that continues in the next chunk.
+/// Whether `mul` is enabled carries over between chunks.
+#[cfg(feature = "std")]
+pub struct Scanner {
+    conditionals: bool,
+    enabled: bool,
+    sum: u64,
+    pending: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl Scanner {
+    /// Follows `do()` and `don't()`, like `part2`.
+    pub fn new() -> Self {
+        Self {
+            conditionals: true,
+            enabled: true,
+            sum: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Ignores `do()` and `don't()`, like `part1`.
+    pub fn without_conditionals() -> Self {
+        Self {
+            conditionals: false,
+            ..Self::new()
+        }
+    }
+
+    /// Scans the next chunk of the program.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.pending.extend_from_slice(chunk);
+        let end = self.pending.len().saturating_sub(MAX_INSTRUCTION_LEN - 1);
+        if end > 0 {
+            self.process(end);
+            self.pending.drain(..end);
+        }
+    }
+
+    /// Scans everything from `reader` and returns the sum so far.
+    pub fn scan<R: BufRead>(&mut self, mut reader: R) -> io::Result<u64> {
+        loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            let len = chunk.len();
+            self.feed(chunk);
+            reader.consume(len);
+        }
+        Ok(self.sum())
+    }
+
+    /// The sum of everything fed so far, including the held back bytes.
+    pub fn sum(&self) -> u64 {
+        let mut sum = self.sum;
+        let mut enabled = self.enabled;
+        self.process_into(self.pending.len(), &mut enabled, &mut sum);
+        sum
+    }
+
+    /// Whether `mul` is enabled after everything fed so far.
+    pub fn enabled(&self) -> bool {
+        let mut sum = 0;
+        let mut enabled = self.enabled;
+        self.process_into(self.pending.len(), &mut enabled, &mut sum);
+        enabled
+    }
+
+    fn process(&mut self, end: usize) {
+        let (mut enabled, mut sum) = (self.enabled, self.sum);
+        self.process_into(end, &mut enabled, &mut sum);
+        (self.enabled, self.sum) = (enabled, sum);
+    }
+
+    fn process_into(&self, end: usize, enabled: &mut bool, sum: &mut u64) {
+        if self.conditionals {
+            process_conditional(&self.pending, end, enabled, sum);
+        } else {
+            process_all_mul(&self.pending, end, sum);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Assumption: all numbers have 1-3 digits
 #[inline(always)]
 fn parse_number(bytes: &[u8]) -> Option<(u64, usize)> {
@@ -154,6 +443,133 @@ mod tests {
         assert_eq!(part2(input), 48);
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_scanner() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let mut scanner = Scanner::new();
+        for chunk in input.as_bytes().chunks(3) {
+            scanner.feed(chunk);
+        }
+        assert_eq!(scanner.sum(), 48);
+        assert!(scanner.enabled());
+
+        let mut scanner = Scanner::without_conditionals();
+        assert_eq!(scanner.scan(input.as_bytes()).unwrap(), 161);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_scanner_chunk_boundaries() {
+        use rand::seq::SliceRandom;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+        use std::io::BufReader;
+
+        let pieces = [
+            "mul(",
+            "123",
+            "4",
+            ",",
+            ")",
+            "do()",
+            "don't()",
+            "mul(7,8)",
+            "mul(123,456)",
+            "x",
+            "d",
+            "m",
+            "mul(1,2)",
+        ];
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..500 {
+            let input: String = (0..rng.gen_range(0..60))
+                .map(|_| *pieces.choose(&mut rng).unwrap())
+                .collect();
+            for capacity in [1, 2, 5, 11, 12, 13, 64] {
+                let reader = BufReader::with_capacity(capacity, input.as_bytes());
+                assert_eq!(
+                    Scanner::new().scan(reader).unwrap(),
+                    part2(&input),
+                    "{input}"
+                );
+                let reader = BufReader::with_capacity(capacity, input.as_bytes());
+                assert_eq!(
+                    Scanner::without_conditionals().scan(reader).unwrap(),
+                    part1(&input),
+                    "{input}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let found: Vec<_> = scan(input, PART2)
+            .into_iter()
+            .map(|m| (PART2[m.instruction].name, m.span, m.operands, m.enabled))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("mul", 1..9, vec![2, 4], true),
+                ("don't", 20..27, vec![], true),
+                ("mul", 28..36, vec![5, 5], false),
+                ("mul", 48..57, vec![11, 8], false),
+                ("do", 59..63, vec![], false),
+                ("mul", 64..72, vec![8, 5], true),
+            ]
+        );
+        assert_eq!(&input[48..57], "mul(11,8)");
+        assert_eq!(evaluate(input, PART1), 161);
+        assert_eq!(evaluate(input, PART2), 48);
+    }
+
+    #[test]
+    fn test_custom_instruction() {
+        const ADD3: Instruction = Instruction {
+            name: "add",
+            arity: 3,
+            min_digits: 2,
+            max_digits: 4,
+            effect: Effect::Accumulate(|operands| operands.iter().sum()),
+        };
+        let input = "add(10,20,1234)add(1,20,30)mul(2,3)don't()add(10,10,10)";
+        assert_eq!(evaluate(input, &[ADD3, MUL]), 1264 + 6 + 30);
+        assert_eq!(evaluate(input, &[ADD3, MUL, DO, DONT]), 1264 + 6);
+    }
+
+    #[test]
+    fn test_evaluate_matches_fast_path() {
+        use rand::seq::SliceRandom;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let pieces = [
+            "mul(",
+            "123",
+            "4",
+            ",",
+            ")",
+            "do()",
+            "don't()",
+            "mul(7,8)",
+            "mul(123,456)",
+            "x",
+            "d",
+            "m",
+        ];
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..2000 {
+            let input: String = (0..rng.gen_range(0..40))
+                .map(|_| *pieces.choose(&mut rng).unwrap())
+                .collect();
+            assert_eq!(evaluate(&input, PART1), part1(&input), "{input}");
+            assert_eq!(evaluate(&input, PART2), part2(&input), "{input}");
+        }
+    }
+
     #[test]
     fn test_compare_part1_with_file() {
         let paths = ["day3.txt", "day3-alt1.txt", "day3-alt2.txt"];
@@ -1,17 +1,20 @@
-use memchr::memchr_iter;
+use crate::input::normalize;
+use alloc::vec::Vec;
+use core::fmt;
+use memchr::{memchr_iter, memmem};
 
-#[aoc(day4, part1, naive)]
+#[cfg_attr(feature = "std", aoc(day4, part1, naive))]
 pub fn part1_naive(input: &str) -> usize {
     part1(input)
 }
 
-#[aoc(day4, part2, naive)]
+#[cfg_attr(feature = "std", aoc(day4, part2, naive))]
 pub fn part2_naive(input: &str) -> usize {
     part2(input)
 }
 
 // Constants based on expected input size
-const GRID_SIZE: usize = 140;
+pub(crate) const GRID_SIZE: usize = 140;
 const INPUT_ROWS: usize = GRID_SIZE;
 const INPUT_COLS: usize = GRID_SIZE + 1; // \n
 const MIN_INPUT_SIZE: usize = INPUT_ROWS * INPUT_COLS - 1; // except the final \n is optional
@@ -31,8 +34,9 @@ const MINUS_UP_RIGHT: (usize, usize, usize) = (DL, 2 * DL, 3 * DL);
 
 const MAS: u32 = u32::from_le_bytes(*b"MAS\0");
 
-#[aoc(day4, part1)]
+#[cfg_attr(feature = "std", aoc(day4, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day4::part1");
     let bytes = input.as_bytes();
 
     debug_assert!(
@@ -44,6 +48,7 @@ pub fn part1(input: &str) -> usize {
 
     let mut count = 0;
 
+    trace_phase!("search");
     for xi in memchr_iter(b'X', bytes) {
         // Consider extra byte for newlines
         let row = xi / INPUT_COLS;
@@ -122,12 +127,14 @@ pub fn part1(input: &str) -> usize {
     count
 }
 
-#[aoc(day4, part2)]
+#[cfg_attr(feature = "std", aoc(day4, part2))]
 pub fn part2(input: &str) -> usize {
+    trace_span!("day4::part2");
     let bytes = input.as_bytes();
     let mut count = 0;
     const M_S: u8 = b'M' ^ b'S';
 
+    trace_phase!("search");
     // Eliminate the need for ever checking up/down boundaries
     for i in memchr_iter(b'A', &bytes[INPUT_COLS..MIN_INPUT_SIZE - INPUT_COLS - 1]) {
         let col = i % INPUT_COLS;
@@ -145,6 +152,353 @@ pub fn part2(input: &str) -> usize {
     count
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+
+    /// `(row, col)` step.
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::UpRight => (-1, 1),
+            Direction::Right => (0, 1),
+            Direction::DownRight => (1, 1),
+            Direction::Down => (1, 0),
+            Direction::DownLeft => (1, -1),
+            Direction::Left => (0, -1),
+            Direction::UpLeft => (-1, -1),
+        }
+    }
+}
+
+/// A word found in the grid, starting at its first letter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// Index into the searched words.
+    pub word: usize,
+    pub row: usize,
+    pub col: usize,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    RaggedRow {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::RaggedRow { row, len, expected } => {
+                write!(f, "row {} has {} cells, expected {}", row, len, expected)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Words up to this length are compared as one packed integer.
+const PACKED_LEN: usize = 8;
+
+/// Finds every occurrence of every word in all 8 directions, grouped by word.
+/// The grid is any number of equally long lines, with LF or CRLF line endings.
+/// Single letter words match once, as `Right`.
+pub fn find_words(grid: &str, words: &[&str]) -> Result<Vec<Match>, ParseError> {
+    let grid = normalize(grid);
+    let bytes = grid.as_bytes();
+    let (rows, cols) = dimensions(&grid)?;
+    let stride = cols + 1;
+
+    let mut matches = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        let word = word.as_bytes();
+        if word.is_empty() || word.len() > rows.max(cols) {
+            continue;
+        }
+        let position = |i: usize| (i / stride, i % stride);
+        let last = word.len() - 1;
+
+        // Horizontal words with SIMD substring search, they can't wrap as words have no `\n`
+        for i in find_overlapping(bytes, word) {
+            let (row, col) = position(i);
+            matches.push(Match {
+                word: index,
+                row,
+                col,
+                direction: Direction::Right,
+            });
+        }
+        if last == 0 {
+            continue;
+        }
+        let reversed: Vec<u8> = word.iter().rev().copied().collect();
+        for i in find_overlapping(bytes, &reversed) {
+            let (row, col) = position(i + last);
+            matches.push(Match {
+                word: index,
+                row,
+                col,
+                direction: Direction::Left,
+            });
+        }
+
+        // The other directions from each first letter
+        let packed = (word.len() <= PACKED_LEN).then(|| pack(word.iter().copied()));
+        for i in memchr_iter(word[0], bytes) {
+            let (row, col) = position(i);
+            for direction in Direction::ALL {
+                let (dr, dc) = direction.delta();
+                if dr == 0 {
+                    continue;
+                }
+                let end_row = row as isize + dr * last as isize;
+                let end_col = col as isize + dc * last as isize;
+                if end_row < 0
+                    || end_row >= rows as isize
+                    || end_col < 0
+                    || end_col >= cols as isize
+                {
+                    continue;
+                }
+
+                let step = dr * stride as isize + dc;
+                let letters =
+                    (0..word.len()).map(|k| bytes[(i as isize + step * k as isize) as usize]);
+                let found = match packed {
+                    Some(packed) => pack(letters) == packed,
+                    None => letters.eq(word.iter().copied()),
+                };
+                if found {
+                    matches.push(Match {
+                        word: index,
+                        row,
+                        col,
+                        direction,
+                    });
+                }
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// `(rows, cols)` of a normalized grid of equally long lines.
+fn dimensions(grid: &str) -> Result<(usize, usize), ParseError> {
+    let cols = grid.lines().next().map_or(0, str::len);
+    if let Some((row, line)) = grid
+        .lines()
+        .enumerate()
+        .find(|(_, line)| line.len() != cols)
+    {
+        return Err(ParseError::RaggedRow {
+            row,
+            len: line.len(),
+            expected: cols,
+        });
+    }
+    Ok((grid.lines().count(), cols))
+}
+
+/// Like `memmem::find_iter`, but also finds overlapping matches such as `ABA` twice in `ABABA`.
+fn find_overlapping<'a>(haystack: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    let finder = memmem::Finder::new(needle);
+    let mut start = 0;
+    core::iter::from_fn(move || {
+        let i = start + finder.find(haystack.get(start..)?)?;
+        start = i + 1;
+        Some(i)
+    })
+}
+
+#[inline(always)]
+fn pack(letters: impl Iterator<Item = u8>) -> u64 {
+    letters.fold(0, |packed, letter| packed << 8 | letter as u64)
+}
+
+/// A small 2D template of letters, with `.` matching anything.
+/// Part 2's X-MAS is `M.S / .A. / M.S` with its rotations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stencil {
+    rows: usize,
+    cols: usize,
+    /// `(row, col, letter)` of every non-wildcard cell, in row-major order.
+    cells: Vec<(usize, usize, u8)>,
+}
+
+impl Stencil {
+    /// Rows are separated by newlines or `/`, surrounding whitespace is ignored.
+    pub fn new(template: &str) -> Self {
+        let lines: Vec<&str> = template
+            .split(['\n', '/'])
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        let cols = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let cells = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.bytes()
+                    .enumerate()
+                    .filter(|&(_, letter)| letter != b'.')
+                    .map(move |(col, letter)| (row, col, letter))
+            })
+            .collect();
+        Self {
+            rows: lines.len(),
+            cols,
+            cells,
+        }
+    }
+
+    /// Turned 90° clockwise.
+    pub fn rotated(&self) -> Self {
+        self.transformed(self.cols, self.rows, |row, col| (col, self.rows - 1 - row))
+    }
+
+    /// Mirrored left to right.
+    pub fn reflected(&self) -> Self {
+        self.transformed(self.rows, self.cols, |row, col| (row, self.cols - 1 - col))
+    }
+
+    /// The distinct rotations and reflections, starting with `self`.
+    pub fn symmetries(&self) -> Vec<Stencil> {
+        let mut variants: Vec<Stencil> = Vec::with_capacity(8);
+        let mut stencil = self.clone();
+        for _ in 0..4 {
+            for variant in [stencil.reflected(), stencil.clone()] {
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+            }
+            stencil = stencil.rotated();
+        }
+        // Keep `self` first
+        let own = variants.iter().position(|v| v == self).unwrap();
+        variants.swap(0, own);
+        variants
+    }
+
+    fn transformed(
+        &self,
+        rows: usize,
+        cols: usize,
+        map: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Self {
+        let mut cells: Vec<_> = self
+            .cells
+            .iter()
+            .map(|&(row, col, letter)| {
+                let (row, col) = map(row, col);
+                (row, col, letter)
+            })
+            .collect();
+        cells.sort_unstable();
+        Self { rows, cols, cells }
+    }
+
+    /// Byte offsets from the top left corner for a grid with `stride` bytes per row,
+    /// like `DOWN_LEFT` and `DOWN_RIGHT` for part 1.
+    pub fn offsets(&self, stride: usize) -> Vec<(usize, u8)> {
+        self.cells
+            .iter()
+            .map(|&(row, col, letter)| (row * stride + col, letter))
+            .collect()
+    }
+}
+
+/// A placed stencil variant, by its top left corner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StencilMatch {
+    /// Index into the searched variants.
+    pub variant: usize,
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Finds every placement of every variant in a grid of equally long lines, like [`find_words`].
+/// Pass `stencil.symmetries()` to include rotations and reflections.
+pub fn find_stencils(grid: &str, variants: &[Stencil]) -> Result<Vec<StencilMatch>, ParseError> {
+    let grid = normalize(grid);
+    let bytes = grid.as_bytes();
+    let (rows, cols) = dimensions(&grid)?;
+    let stride = cols + 1;
+
+    let mut matches = Vec::new();
+    for (index, stencil) in variants.iter().enumerate() {
+        if stencil.rows > rows || stencil.cols > cols {
+            continue;
+        }
+        let (last_row, last_col) = (rows - stencil.rows, cols - stencil.cols);
+        let offsets = stencil.offsets(stride);
+        let fits = |corner: usize| {
+            offsets
+                .iter()
+                .all(|&(offset, letter)| unsafe { *bytes.get_unchecked(corner + offset) } == letter)
+        };
+
+        let Some(&(anchor, letter)) = offsets.first() else {
+            // Only wildcards, fits everywhere
+            for row in 0..=last_row {
+                for col in 0..=last_col {
+                    matches.push(StencilMatch {
+                        variant: index,
+                        row,
+                        col,
+                    });
+                }
+            }
+            continue;
+        };
+        for i in memchr_iter(letter, bytes) {
+            let Some(corner) = i.checked_sub(anchor) else {
+                continue;
+            };
+            let (row, col) = (corner / stride, corner % stride);
+            // The corner bounds keep every offset inside the grid
+            if row <= last_row && col <= last_col && fits(corner) {
+                matches.push(StencilMatch {
+                    variant: index,
+                    row,
+                    col,
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+/// Number of placements of the stencil in any rotation or reflection.
+pub fn count_stencil(grid: &str, stencil: &Stencil) -> Result<usize, ParseError> {
+    Ok(find_stencils(grid, &stencil.symmetries())?.len())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,6 +580,178 @@ MXMXAXMASX";
     }
      */
 
+    #[test]
+    fn test_find_words_example() {
+        let matches = find_words(INPUT, &["XMAS", "MAS"]).unwrap();
+        assert_eq!(matches.iter().filter(|m| m.word == 0).count(), 18);
+        assert!(matches.contains(&Match {
+            word: 0,
+            row: 0,
+            col: 5,
+            direction: Direction::Right
+        }));
+        assert!(matches.contains(&Match {
+            word: 0,
+            row: 9,
+            col: 9,
+            direction: Direction::UpLeft
+        }));
+    }
+
+    #[test]
+    fn test_find_words_directions() {
+        let grids = [
+            ("....\nSAMX\n....\n....", 1, 3, Direction::Left),
+            ("....\n....\nXMAS\n....", 2, 0, Direction::Right),
+            ("S...\nA...\nM...\nX...", 3, 0, Direction::Up),
+            ("X...\nM...\nA...\nS...", 0, 0, Direction::Down),
+            ("S...\n.A..\n..M.\n...X", 3, 3, Direction::UpLeft),
+            ("...S\n..A.\n.M..\nX...", 3, 0, Direction::UpRight),
+            ("X...\n.M..\n..A.\n...S", 0, 0, Direction::DownRight),
+            ("...X\n..M.\n.A..\nS...", 0, 3, Direction::DownLeft),
+        ];
+        for (grid, row, col, direction) in grids {
+            let expected = Match {
+                word: 0,
+                row,
+                col,
+                direction,
+            };
+            assert_eq!(find_words(grid, &["XMAS"]).unwrap(), [expected], "{grid}");
+        }
+    }
+
+    #[test]
+    fn test_find_words_matches_naive() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..200 {
+            let (rows, cols) = (rng.gen_range(1..12), rng.gen_range(1..12));
+            let cells: Vec<Vec<u8>> = (0..rows)
+                .map(|_| (0..cols).map(|_| b"AB"[rng.gen_range(0..2)]).collect())
+                .collect();
+            let grid = cells
+                .iter()
+                .map(|row| String::from_utf8(row.clone()).unwrap())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let words = ["AB", "ABA", "BBAAB", "ABABABABAB"];
+
+            let mut expected = Vec::new();
+            for (word_index, word) in words.iter().enumerate() {
+                for (row, col, direction) in (0..rows)
+                    .flat_map(|r| (0..cols).map(move |c| (r, c)))
+                    .flat_map(|(r, c)| Direction::ALL.map(|d| (r, c, d)))
+                {
+                    let (dr, dc) = direction.delta();
+                    let found = word.bytes().enumerate().all(|(k, letter)| {
+                        let r = row as isize + dr * k as isize;
+                        let c = col as isize + dc * k as isize;
+                        r >= 0
+                            && c >= 0
+                            && cells.get(r as usize).and_then(|line| line.get(c as usize))
+                                == Some(&letter)
+                    });
+                    if found {
+                        expected.push(Match {
+                            word: word_index,
+                            row,
+                            col,
+                            direction,
+                        });
+                    }
+                }
+            }
+
+            let mut matches = find_words(&grid, &words).unwrap();
+            let key = |m: &Match| (m.word, m.row, m.col, m.direction as u8);
+            matches.sort_by_key(key);
+            expected.sort_by_key(key);
+            assert_eq!(matches, expected, "{grid}");
+        }
+    }
+
+    #[test]
+    fn test_stencil_symmetries() {
+        let x_mas = Stencil::new("M.S / .A. / M.S");
+        assert_eq!(
+            x_mas.offsets(11),
+            [(0, b'M'), (2, b'S'), (12, b'A'), (22, b'M'), (24, b'S')]
+        );
+        let variants = x_mas.symmetries();
+        assert_eq!(variants.len(), 4);
+        assert_eq!(variants[0], x_mas);
+        assert!(variants.contains(&Stencil::new("M.M\n.A.\nS.S")));
+
+        let l = Stencil::new("X.\nX.\nXX");
+        assert_eq!(l.symmetries().len(), 8);
+        assert_eq!(l.rotated().rotated().rotated().rotated(), l);
+        assert_eq!(Stencil::new("XX").symmetries().len(), 2);
+    }
+
+    #[test]
+    fn test_find_stencils() {
+        let x_mas = Stencil::new("M.S\n.A.\nM.S");
+        assert_eq!(count_stencil(INPUT, &x_mas).unwrap(), 9);
+
+        let matches = find_stencils(INPUT, &[x_mas]).unwrap();
+        assert!(matches.contains(&StencilMatch {
+            variant: 0,
+            row: 0,
+            col: 1
+        }));
+
+        // Straight XMAS in 4 of the directions
+        assert_eq!(
+            count_stencil(INPUT, &Stencil::new("XMAS")).unwrap(),
+            3 + 2 + 1 + 2
+        );
+        assert_eq!(count_stencil("AB\nCD", &Stencil::new("..\n..")).unwrap(), 1);
+        assert_eq!(count_stencil("AB\nCD", &Stencil::new("ABC")).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_grid_layout() {
+        let crlf = INPUT.replace('\n', "\r\n");
+        assert_eq!(
+            find_words(&crlf, &["XMAS"]).unwrap(),
+            find_words(INPUT, &["XMAS"]).unwrap()
+        );
+        let x_mas = Stencil::new("M.S/.A./M.S");
+        assert_eq!(count_stencil(&crlf, &x_mas).unwrap(), 9);
+        assert_eq!(
+            find_words("AB\nC\nDE", &["AB"]).unwrap_err(),
+            ParseError::RaggedRow {
+                row: 1,
+                len: 1,
+                expected: 2
+            }
+        );
+        assert!(count_stencil("AB\nCDE", &x_mas).is_err());
+    }
+
+    #[test]
+    fn test_count_stencil_matches_part2() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(4);
+        let grid = (0..GRID_SIZE)
+            .map(|_| {
+                (0..GRID_SIZE)
+                    .map(|_| b"XMAS"[rng.gen_range(0..4)] as char)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(
+            count_stencil(&grid, &Stencil::new("M.S/.A./M.S")).unwrap(),
+            part2(&grid)
+        );
+    }
+
     #[test]
     fn test_compare_part1_with_file() {
         let paths = [
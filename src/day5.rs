@@ -1,27 +1,42 @@
+use crate::input::{fits_layout, normalize};
+use alloc::collections::BinaryHeap;
+#[cfg(not(feature = "std"))]
+use alloc::collections::{BTreeMap as FxHashMap, BTreeSet as FxHashSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
 use memchr::memchr_iter;
+#[cfg(feature = "std")]
+use rustc_hash::{FxHashMap, FxHashSet};
 
-#[aoc(day5, part1, naive)]
+#[cfg_attr(feature = "std", aoc(day5, part1, naive))]
 pub fn part1_naive(input: &str) -> u32 {
     part1(input)
 }
 
-#[aoc(day5, part2, naive)]
+#[cfg_attr(feature = "std", aoc(day5, part2, naive))]
 pub fn part2_naive(input: &str) -> usize {
     part2(input)
 }
 
-#[aoc(day5, part1)]
+#[cfg_attr(feature = "std", aoc(day5, part1))]
 pub fn part1(input: &str) -> u32 {
+    trace_span!("day5::part1");
     unsafe { impl1(input) }
 }
 
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut GREATER: [u8; 10000] = [0; 10000];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut GREATER_CNT: [u128; 100] = [0; 100];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u8 = 0;
 
 unsafe fn impl1(input: &str) -> u32 {
     TRUE = TRUE.wrapping_add(1);
+    trace_phase!("build tables");
     let bytes = parse_orderings(input.as_bytes());
+    trace_phase!("check");
     let mut sum: u32 = 0;
     let mut prev_npos = 0;
     for npos in memchr_iter(b'\n', bytes) {
@@ -44,14 +59,17 @@ unsafe fn get_mid_if_valid(bytes: &[u8]) -> u32 {
     take_one(&bytes[mid_start..])
 }
 
-#[aoc(day5, part2)]
+#[cfg_attr(feature = "std", aoc(day5, part2))]
 pub fn part2(input: &str) -> usize {
+    trace_span!("day5::part2");
     unsafe { impl2(input) }
 }
 
 unsafe fn impl2(input: &str) -> usize {
     TRUE = TRUE.wrapping_add(1);
+    trace_phase!("build tables");
     let mut bytes = parse_orderings_with_cnt(input.as_bytes());
+    trace_phase!("reorder");
     let mut sum: usize = 0;
 
     while bytes.len() >= 6 {
@@ -171,6 +189,224 @@ unsafe fn take_two_reversed(b: &[u8]) -> usize {
         - 53328
 }
 
+/// A rule `before|after` broken by an update.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuleViolation {
+    pub before: u32,
+    pub after: u32,
+    /// Positions of `after` and `before` in the update, `after` comes first.
+    pub positions: (usize, usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateReport {
+    pub pages: Vec<u32>,
+    /// Every broken rule, by position.
+    pub violations: Vec<RuleViolation>,
+}
+
+impl UpdateReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Lists the broken rules of every update, in input order.
+/// `None` unless the input has the layout `part2` reads, two digit pages included.
+pub fn explain(input: &str) -> Option<Vec<UpdateReport>> {
+    trace_span!("day5::explain");
+    let input = normalize(input);
+    if !fits_layout(5, &input) {
+        return None;
+    }
+    Some(unsafe { explain_impl(&input) })
+}
+
+unsafe fn explain_impl(input: &str) -> Vec<UpdateReport> {
+    TRUE = TRUE.wrapping_add(1);
+    trace_phase!("build tables");
+    let bytes = parse_orderings_with_cnt(input.as_bytes());
+    trace_phase!("explain");
+    let mut reports = Vec::new();
+    let mut positions = [0usize; 100];
+
+    for line in bytes.split(|&b| b == b'\n').filter(|line| line.len() >= 2) {
+        let pages: Vec<usize> = line.chunks(3).map(|page| take_one_usize(page)).collect();
+        for (i, &page) in pages.iter().enumerate() {
+            positions[page] = i;
+        }
+
+        // Pages after `i` that have to come before it
+        let mut violations = Vec::new();
+        let mut later: u128 = 0;
+        for (i, &page) in pages.iter().enumerate().rev() {
+            let mut broken = GREATER_CNT[page] & later;
+            while broken != 0 {
+                let before = broken.trailing_zeros() as usize;
+                broken &= broken - 1;
+                violations.push(RuleViolation {
+                    before: before as u32,
+                    after: page as u32,
+                    positions: (i, positions[before]),
+                });
+            }
+            later |= 1u128 << page;
+        }
+        violations.sort_unstable_by_key(|violation| violation.positions);
+
+        reports.push(UpdateReport {
+            pages: pages.iter().map(|&page| page as u32).collect(),
+            violations,
+        });
+    }
+    reports
+}
+
+/// Page ordering rules for any page IDs, without assuming the rules form a total order.
+#[derive(Clone, Debug, Default)]
+pub struct Rules {
+    /// `(a, b)` for every rule `a|b`.
+    pairs: FxHashSet<(u64, u64)>,
+    /// Pages that must come after each page.
+    after: FxHashMap<u64, Vec<u64>>,
+}
+
+/// Pages in a cycle of rules, each before the next and the last before the first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle(pub Vec<u64>);
+
+impl Rules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the rule `before|after`.
+    pub fn add(&mut self, before: u64, after: u64) {
+        if self.pairs.insert((before, after)) {
+            self.after.entry(before).or_default().push(after);
+        }
+    }
+
+    /// Whether `before|after` is a rule.
+    pub fn requires(&self, before: u64, after: u64) -> bool {
+        self.pairs.contains(&(before, after))
+    }
+
+    /// Parses rules and updates. Unlike `part1` and `part2`, page IDs may have any number of digits.
+    pub fn parse(input: &str) -> (Self, Vec<Vec<u64>>) {
+        let mut rules = Self::new();
+        let mut lines = input.lines();
+        for line in lines.by_ref().take_while(|line| !line.is_empty()) {
+            let (before, after) = line.split_once('|').expect("Rules are `a|b`");
+            rules.add(before.parse().unwrap(), after.parse().unwrap());
+        }
+        let updates = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| line.split(',').map(|page| page.parse().unwrap()).collect())
+            .collect();
+        (rules, updates)
+    }
+
+    /// Whether no rule puts a later page of the update before an earlier one.
+    pub fn is_ordered(&self, update: &[u64]) -> bool {
+        update.iter().enumerate().all(|(i, &page)| {
+            update[i + 1..]
+                .iter()
+                .all(|&later| !self.requires(later, page))
+        })
+    }
+
+    /// A cycle among all rules, if there is one. The puzzle's rules usually have cycles,
+    /// only the rules between the pages of one update need to be acyclic.
+    pub fn find_cycle(&self) -> Option<Cycle> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            Visiting,
+            Done,
+        }
+        let mut states: FxHashMap<u64, State> = FxHashMap::default();
+
+        for &root in self.after.keys() {
+            if states.contains_key(&root) {
+                continue;
+            }
+            // Depth first, `path` holds the pages being visited and their next child
+            let mut path: Vec<(u64, usize)> = vec![(root, 0)];
+            states.insert(root, State::Visiting);
+            while let Some((page, child)) = path.last_mut() {
+                let page = *page;
+                let Some(&next) = self.after.get(&page).and_then(|after| after.get(*child)) else {
+                    states.insert(page, State::Done);
+                    path.pop();
+                    continue;
+                };
+                *child += 1;
+                match states.get(&next) {
+                    Some(State::Visiting) => {
+                        let start = path.iter().position(|&(p, _)| p == next).unwrap();
+                        return Some(Cycle(path[start..].iter().map(|&(p, _)| p).collect()));
+                    }
+                    Some(State::Done) => {}
+                    None => {
+                        states.insert(next, State::Visiting);
+                        path.push((next, 0));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// The update reordered to follow every rule between its pages, other rules are ignored.
+    /// Pages that the rules don't order keep their relative order, so ordered updates are
+    /// returned unchanged.
+    pub fn correct(&self, update: &[u64]) -> Result<Vec<u64>, Cycle> {
+        let n = update.len();
+        // Kahn's algorithm over positions, taking the earliest free position first
+        let mut in_degree = vec![0usize; n];
+        let mut successors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for i in 0..n {
+            for j in 0..n {
+                if self.requires(update[i], update[j]) {
+                    successors[i].push(j);
+                    in_degree[j] += 1;
+                }
+            }
+        }
+
+        let mut free: BinaryHeap<Reverse<usize>> =
+            (0..n).filter(|&i| in_degree[i] == 0).map(Reverse).collect();
+        let mut order = Vec::with_capacity(n);
+        while let Some(Reverse(i)) = free.pop() {
+            order.push(update[i]);
+            for &j in &successors[i] {
+                in_degree[j] -= 1;
+                if in_degree[j] == 0 {
+                    free.push(Reverse(j));
+                }
+            }
+        }
+        if order.len() == n {
+            return Ok(order);
+        }
+
+        // Every position left has a predecessor that is also left, walk back until one repeats
+        let mut seen = vec![usize::MAX; n];
+        let mut walk = Vec::new();
+        let mut i = (0..n).find(|&i| in_degree[i] > 0).unwrap();
+        while seen[i] == usize::MAX {
+            seen[i] = walk.len();
+            walk.push(update[i]);
+            i = (0..n)
+                .find(|&j| in_degree[j] > 0 && self.requires(update[j], update[i]))
+                .unwrap();
+        }
+        let mut cycle = walk.split_off(seen[i]);
+        cycle.reverse();
+        Err(Cycle(cycle))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,6 +464,101 @@ mod tests {
         assert_eq!(part2(INPUT), 123);
     }
 
+    #[test]
+    fn test_rules_example() {
+        let (rules, updates) = Rules::parse(INPUT);
+        let mut sums = [0, 0];
+        for update in &updates {
+            let corrected = rules.correct(update).unwrap();
+            assert!(rules.is_ordered(&corrected));
+            let ordered = rules.is_ordered(update);
+            assert_eq!(ordered, &corrected == update);
+            sums[!ordered as usize] += corrected[corrected.len() / 2];
+        }
+        assert_eq!(sums, [143, 123]);
+        assert_eq!(
+            rules.correct(&[97, 13, 75, 29, 47]).unwrap(),
+            [97, 75, 47, 29, 13]
+        );
+        assert_eq!(rules.find_cycle(), None);
+    }
+
+    #[test]
+    fn test_rules_partial_order_and_cycles() {
+        let mut rules = Rules::new();
+        rules.add(1000, 7);
+        rules.add(7, 123456);
+        // Unrelated pages keep their order around the constrained ones
+        assert_eq!(
+            rules.correct(&[5, 123456, 9, 7, 1000]).unwrap(),
+            [5, 9, 1000, 7, 123456]
+        );
+        // Like in the puzzle, rules through pages outside the update don't count
+        assert_eq!(rules.correct(&[123456, 1000]).unwrap(), [123456, 1000]);
+
+        rules.add(123456, 1000);
+        let Some(Cycle(cycle)) = rules.find_cycle() else {
+            panic!("No cycle found");
+        };
+        assert_eq!(cycle.len(), 3);
+        for (i, &page) in cycle.iter().enumerate() {
+            assert!(rules.requires(page, cycle[(i + 1) % cycle.len()]));
+        }
+        let Err(Cycle(cycle)) = rules.correct(&[42, 7, 123456, 1000]) else {
+            panic!("No cycle found");
+        };
+        assert_eq!(cycle.len(), 3);
+        for (i, &page) in cycle.iter().enumerate() {
+            assert!(rules.requires(page, cycle[(i + 1) % cycle.len()]));
+        }
+        // Without 1000 the rules between the pages are acyclic
+        assert_eq!(rules.correct(&[123456, 7]).unwrap(), [7, 123456]);
+    }
+
+    #[test]
+    fn test_explain_rejects_other_layouts() {
+        // A one digit page, a three digit page and a missing update section
+        assert_eq!(explain("47|53\n\n75,4\n"), None);
+        assert_eq!(explain("47|53\n\n75,128\n"), None);
+        assert_eq!(explain("47|53\n"), None);
+        assert_eq!(explain("47|53\r\n\r\n53,47\r\n").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_explain() {
+        let reports = explain(INPUT).unwrap();
+        let valid: Vec<bool> = reports.iter().map(UpdateReport::is_valid).collect();
+        assert_eq!(valid, [true, true, true, false, false, false]);
+
+        let violation = |before, after, positions| RuleViolation {
+            before,
+            after,
+            positions,
+        };
+        assert_eq!(reports[3].violations, [violation(97, 75, (0, 1))]);
+        assert_eq!(reports[4].violations, [violation(29, 13, (1, 2))]);
+        assert_eq!(
+            reports[5].violations,
+            [
+                violation(75, 13, (1, 2)),
+                violation(29, 13, (1, 3)),
+                violation(47, 13, (1, 4)),
+                violation(47, 29, (3, 4)),
+            ]
+        );
+
+        // Agrees with the general rules engine
+        let (rules, updates) = Rules::parse(INPUT);
+        for (report, update) in reports.iter().zip(&updates) {
+            let expected: Vec<_> = (0..update.len())
+                .flat_map(|i| (i + 1..update.len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| rules.requires(update[j], update[i]))
+                .map(|(i, j)| violation(update[j] as u32, update[i] as u32, (i, j)))
+                .collect();
+            assert_eq!(report.violations, expected);
+        }
+    }
+
     #[test]
     fn test_compare_part1_with_file() {
         let paths = [
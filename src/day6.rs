@@ -1,9 +1,16 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeSet as HashSet;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::collections::HashSet;
 
 /// Directions represented as (delta_row, delta_col)
 const DIRECTIONS: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
 
-#[aoc(day6, part1, naive)]
+#[cfg_attr(feature = "std", aoc(day6, part1, naive))]
 pub fn part1_naive(input: &str) -> usize {
     part1_impl(input).0
 }
@@ -108,13 +115,11 @@ fn part1_impl(input: &str) -> (usize, Vec<(usize, usize)>, usize, usize) {
 // ^ for the above to work, we'd also need vertical bitmask (too bad 130 bit doesn't fit in u128) - AHA! there's no way to enter the 1st or 130th row/column without exiting the grid
 // because hitting the wall redirects the guard. we only need booleans if we have walls on left, right, up, down
 
-#[aoc(day6, part2, naive)]
+#[cfg_attr(feature = "std", aoc(day6, part2, naive))]
 pub fn part2_naive(input: &str) -> usize {
     part1(input)
 }
 
-const GRID_SIZE: usize = 130;
-
 // Idea: directions (d) are encoded as 0: up, 1: right, 2: down, 3: left
 // To get vertical/horizontal bitmask we take d % 2 as index of `grid`
 // To get the wall ahead in our current direction we simply use d as index of `walls`
@@ -127,16 +132,121 @@ const VERTICAL: usize = 0;
 const HORIZONTAL: usize = 1;
 
 const MIN_POS: usize = 0;
-const MAX_POS: usize = GRID_SIZE - 3;
 
-static mut VISITED: [u8; 128 * 128 * 4] = [0; 128 * 128 * 4];
+#[cfg_attr(feature = "parallel", thread_local)]
 // i, j, direction
-// static mut VISITED: [[[u8; 128]; 128]; 4] = [[[0; 128]; 128]; 4];
+static mut VISITED: Vec<u8> = Vec::new();
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut TRUE: u8 = 0;
 
-#[inline(always)]
-fn visited_index(i: usize, j: usize, direction: usize) -> usize {
-    (i * 128 + j) * 4 + direction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The map needs a border and at least one cell inside it.
+    TooSmall {
+        rows: usize,
+        cols: usize,
+    },
+    RaggedRow {
+        row: usize,
+        len: usize,
+        expected: usize,
+    },
+    NoGuard,
+    MultipleGuards,
+    /// The guard has to start inside the border, `part2` pads such maps with empty cells.
+    GuardOnEdge {
+        row: usize,
+        col: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::TooSmall { rows, cols } => {
+                write!(f, "map is {}x{}, needs at least 3x3", rows, cols)
+            }
+            ParseError::RaggedRow { row, len, expected } => {
+                write!(f, "row {} has {} cells, expected {}", row, len, expected)
+            }
+            ParseError::NoGuard => write!(f, "guard's starting position '^' not found"),
+            ParseError::MultipleGuards => write!(f, "multiple starting positions '^' found"),
+            ParseError::GuardOnEdge { row, col } => {
+                write!(f, "guard starts on the edge at row {}, column {}", row, col)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Fixed length bitsets, one per line, stored in `words` u64s each.
+#[derive(Debug, Clone)]
+pub struct BitLines {
+    len: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl BitLines {
+    pub fn new(lines: usize, len: usize) -> Self {
+        let words = len.div_ceil(64);
+        Self {
+            len,
+            words,
+            bits: vec![0; lines * words],
+        }
+    }
+
+    #[inline(always)]
+    fn line(&self, line: usize) -> &[u64] {
+        &self.bits[line * self.words..(line + 1) * self.words]
+    }
+
+    #[inline(always)]
+    pub fn set(&mut self, line: usize, pos: usize) {
+        assert!(pos < self.len, "position {pos} is past the line end");
+        self.bits[line * self.words + pos / 64] |= 1 << (pos % 64);
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self, line: usize, pos: usize) {
+        assert!(pos < self.len, "position {pos} is past the line end");
+        self.bits[line * self.words + pos / 64] &= !(1 << (pos % 64));
+    }
+
+    /// The first set bit after `pos`.
+    #[inline(always)]
+    pub fn next_after(&self, line: usize, pos: usize) -> Option<usize> {
+        let words = self.line(line);
+        let start = pos + 1;
+        let mut w = start / 64;
+        let mut word = *words.get(w)? & (!0u64 << (start % 64));
+        loop {
+            if word != 0 {
+                return Some(w * 64 + word.trailing_zeros() as usize);
+            }
+            w += 1;
+            word = *words.get(w)?;
+        }
+    }
+
+    /// The last set bit before `pos`.
+    #[inline(always)]
+    pub fn prev_before(&self, line: usize, pos: usize) -> Option<usize> {
+        let words = self.line(line);
+        let end = pos.checked_sub(1)?;
+        let mut w = end / 64;
+        let mut word = words[w] & (!0u64 >> (63 - end % 64));
+        loop {
+            if word != 0 {
+                return Some(w * 64 + 63 - word.leading_zeros() as usize);
+            }
+            w = w.checked_sub(1)?;
+            word = words[w];
+        }
+    }
 }
 
 /// Represents the parsed grid with optimized data structures.
@@ -149,122 +259,110 @@ fn visited_index(i: usize, j: usize, direction: usize) -> usize {
 ///
 /// To get the vertical/horizontal bitmask, take `d % 2` as the index of `grid`.
 /// To get the wall ahead in the current direction, use `d` as the index of `walls`.
-#[derive(Debug)]
+///
+/// The guard can only enter the border to leave the map, so the border is kept as walls
+/// and only the inner `rows` x `cols` cells are bitboards.
+///
+/// The fields are private: `is_looping` relies on `start_pos` and every obstacle being inside
+/// the inner grid, which `parse_input` and `add_obstacle` check.
+#[derive(Debug, Clone)]
 pub struct Grid {
     /// 1st index is the direction (0: up, 1: right, 2: down, 3: left)
-    /// 2nd index is the column/row inner index (0 to 127):
+    /// 2nd index is the column/row inner index:
     /// For up/down - j, for left/right - i
-    pub walls: [[bool; 128]; 4],
+    walls: [Vec<bool>; 4],
 
     /// 1st index is 0: vertical, 1: horizontal
-    /// 2nd index is the column/row index (0 to 127):
-    /// For vertical - j, for horizontal - i
-    pub grid: [[u128; 128]; 2],
+    /// Lines are columns (j) for vertical and rows (i) for horizontal
+    grid: [BitLines; 2],
 
     /// Starting position of the guard within the inner grid (0-based indexing)
-    pub start_pos: (usize, usize),
+    start_pos: (usize, usize),
+
+    /// Size of the inner grid
+    rows: usize,
+    cols: usize,
 }
 
 impl Grid {
-    /// Parses the input string into a `Grid` struct.
-    ///
-    /// # Arguments
-    ///
-    /// * `input` - A string slice representing the grid.
-    ///
-    /// # Panics
-    ///
-    /// * Panics if the input is too short.
-    /// * Panics if the guard's starting position is missing or duplicated.
-    /// * Panics if newline characters are missing where expected.
-    pub fn parse_input(input: &str) -> Self {
-        // Each line has 130 characters plus a newline, totaling 131 bytes.
-        // The last line may omit the newline, so we allow up to 130 * 131 - 1 bytes.
-        debug_assert!(
-            input.len() >= GRID_SIZE * (GRID_SIZE + 1) - 1,
-            "Input is too short. Expected at least {} bytes, found {}.",
-            GRID_SIZE * (GRID_SIZE + 1) - 1,
-            input.len()
-        );
-
-        // Initialize all fields
-        let mut walls = [[false; 128]; 4];
-        let mut grid = [[0u128; 128]; 2];
-        let mut start_pos = (usize::MAX, usize::MAX); // Placeholder
-
-        // Convert the input string to bytes for efficient indexing
-        let bytes = input.as_bytes();
-
-        for i in 0..GRID_SIZE {
-            // Calculate the start and end indices for the current line
-            let line_start = i * (GRID_SIZE + 1);
-
-            // Access the current line slice
-            let line = &bytes[line_start..];
+    /// Parses a rectangular map of any size.
+    pub fn parse_input(input: &str) -> Result<Self, ParseError> {
+        let lines: Vec<&[u8]> = input.lines().map(str::as_bytes).collect();
+        let (outer_rows, outer_cols) = (lines.len(), lines.first().map_or(0, |l| l.len()));
+        if outer_rows < 3 || outer_cols < 3 {
+            return Err(ParseError::TooSmall {
+                rows: outer_rows,
+                cols: outer_cols,
+            });
+        }
+        if let Some(row) = lines.iter().position(|line| line.len() != outer_cols) {
+            return Err(ParseError::RaggedRow {
+                row,
+                len: lines[row].len(),
+                expected: outer_cols,
+            });
+        }
 
-            // Process top_wall (line 0) and bottom_wall (line 129)
-            if i == 0 {
-                // Top wall: check columns 1 to 128 (indices 1 to 128)
-                for j in 1..=GRID_SIZE - 2 {
-                    if line[j] == b'#' {
-                        walls[UP][j - 1] = true;
-                    }
-                }
-                continue;
-            } else if i == GRID_SIZE - 1 {
-                // Bottom wall: check columns 1 to 128 (indices 1 to 128)
-                for j in 1..=GRID_SIZE - 2 {
-                    if line[j] == b'#' {
-                        walls[DOWN][j - 1] = true;
+        let (rows, cols) = (outer_rows - 2, outer_cols - 2);
+        let mut walls = [
+            vec![false; cols],
+            vec![false; rows],
+            vec![false; cols],
+            vec![false; rows],
+        ];
+        let mut grid = [BitLines::new(cols, rows), BitLines::new(rows, cols)];
+        let mut start_pos = None;
+
+        for (i, line) in lines.iter().enumerate() {
+            for (j, &byte) in line.iter().enumerate() {
+                let on_edge = i == 0 || j == 0 || i == outer_rows - 1 || j == outer_cols - 1;
+                match byte {
+                    b'^' if on_edge => return Err(ParseError::GuardOnEdge { row: i, col: j }),
+                    b'^' if start_pos.is_some() => return Err(ParseError::MultipleGuards),
+                    b'^' => start_pos = Some((i - 1, j - 1)),
+                    b'#' if on_edge => {
+                        // Corners can't be reached, they never stop the guard
+                        if i == 0 && (1..=cols).contains(&j) {
+                            walls[UP][j - 1] = true;
+                        } else if i == outer_rows - 1 && (1..=cols).contains(&j) {
+                            walls[DOWN][j - 1] = true;
+                        } else if j == 0 && (1..=rows).contains(&i) {
+                            walls[LEFT][i - 1] = true;
+                        } else if j == outer_cols - 1 && (1..=rows).contains(&i) {
+                            walls[RIGHT][i - 1] = true;
+                        }
                     }
-                }
-                continue;
-            }
-
-            // Inner rows: 1 to 128 (0-based indexing: 1..=128)
-            let inner_row = i - 1; // 0-based index for inner rows (0..127)
-
-            // Check left_wall and right_wall for this row
-            if line[0] == b'#' {
-                walls[LEFT][inner_row] = true;
-            }
-            if line[GRID_SIZE - 1] == b'#' {
-                walls[RIGHT][inner_row] = true;
-            }
-
-            // Process inner columns: 1 to 128 (indices 1 to 128)
-            for j in 1..=GRID_SIZE - 2 {
-                let byte = line[j];
-                let col_bit = j - 1; // 0-based index for columns (0..127)
-
-                if byte == b'#' {
-                    // Set the bit for this column in lines_bitmask (horizontal)
-                    grid[HORIZONTAL][inner_row] |= 1 << col_bit;
-
-                    // Set the bit for this row in columns_bitmask (vertical)
-                    grid[VERTICAL][col_bit] |= 1 << inner_row;
-                } else if byte == b'^' {
-                    // Record the starting position (0-based indexing)
-                    if start_pos.0 != usize::MAX || start_pos.1 != usize::MAX {
-                        panic!("Multiple starting positions '^' found in the grid.");
+                    b'#' => {
+                        grid[HORIZONTAL].set(i - 1, j - 1);
+                        grid[VERTICAL].set(j - 1, i - 1);
                     }
-                    start_pos = (inner_row, col_bit);
+                    // Ignore other characters ('.' or any others)
+                    _ => {}
                 }
-                // Ignore other characters ('.' or any others)
             }
         }
 
-        // After parsing, ensure that the start_pos was found
-        debug_assert!(
-            start_pos.0 != usize::MAX && start_pos.1 != usize::MAX,
-            "Guard's starting position '^' not found in the grid."
-        );
-
-        Grid {
+        Ok(Grid {
             walls,
             grid,
-            start_pos,
-        }
+            start_pos: start_pos.ok_or(ParseError::NoGuard)?,
+            rows,
+            cols,
+        })
+    }
+
+    pub fn add_obstacle(&mut self, i: usize, j: usize) {
+        assert!(
+            i < self.rows && j < self.cols,
+            "obstacle ({i}, {j}) is outside the inner grid"
+        );
+        self.grid[VERTICAL].set(j, i);
+        self.grid[HORIZONTAL].set(i, j);
+    }
+
+    pub fn remove_obstacle(&mut self, i: usize, j: usize) {
+        self.grid[VERTICAL].clear(j, i);
+        self.grid[HORIZONTAL].clear(i, j);
     }
 
     /// Finds the distance to the next obstacle in the given direction from position (i, j).
@@ -280,91 +378,40 @@ impl Grid {
     /// * `Option<usize>` - Some(distance) if an obstacle is found, None otherwise.
     #[inline(always)]
     fn find_next_obstacle_distance(&self, i: usize, j: usize, d: usize) -> Option<usize> {
-        let bitmask_i = d % 2; // 0: vertical, 1: horizontal
-        let bitmask_j = (i * bitmask_i) + (j * (1 - bitmask_i)); // i if horizontal, j if vertical
-        let bitmask = self.grid[bitmask_i][bitmask_j];
         match d {
-            UP => {
-                if i == 0 {
-                    // At the top edge, no movement possible
-                    return None;
-                }
-                let mask = bitmask & ((1u128 << i) - 1);
-                if mask != 0 {
-                    let leading = mask.leading_zeros();
-                    let obstacle_pos = 127 - leading as usize;
-                    if obstacle_pos < i {
-                        Some(i - obstacle_pos - 1)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            }
-            RIGHT => {
-                if j == MAX_POS {
-                    // At the right edge, no movement possible
-                    return None;
-                }
-                let mask = (bitmask & (!0u128 << (j + 1))) >> (j + 1);
-                if mask != 0 {
-                    let trailing = mask.trailing_zeros();
-                    let obstacle_pos = trailing as usize + j + 1;
-                    if obstacle_pos <= MAX_POS {
-                        Some(obstacle_pos - j - 1)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            }
-            DOWN => {
-                if i == MAX_POS {
-                    // At the bottom edge, no movement possible
-                    return None;
-                }
-                let mask = (bitmask & (!0u128 << (i + 1))) >> (i + 1);
-                if mask != 0 {
-                    let trailing = mask.trailing_zeros();
-                    let obstacle_pos = trailing as usize + i + 1;
-                    if obstacle_pos <= MAX_POS {
-                        Some(obstacle_pos - i - 1)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            }
-            LEFT => {
-                if j == 0 {
-                    // At the left edge, no movement possible
-                    return None;
-                }
-                let mask = bitmask & ((1u128 << j) - 1);
-                if mask != 0 {
-                    let leading = mask.leading_zeros();
-                    let obstacle_pos = 127 - leading as usize;
-                    if obstacle_pos < j {
-                        Some(j - obstacle_pos - 1)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            }
+            UP => self.grid[VERTICAL]
+                .prev_before(j, i)
+                .map(|obstacle_pos| i - obstacle_pos - 1),
+            RIGHT => self.grid[HORIZONTAL]
+                .next_after(i, j)
+                .map(|obstacle_pos| obstacle_pos - j - 1),
+            DOWN => self.grid[VERTICAL]
+                .next_after(j, i)
+                .map(|obstacle_pos| obstacle_pos - i - 1),
+            LEFT => self.grid[HORIZONTAL]
+                .prev_before(i, j)
+                .map(|obstacle_pos| j - obstacle_pos - 1),
             _ => unreachable!(), // Directions are encoded from 0 to 3
         }
     }
 
+    /// Walks the guard from the start, returns whether it loops and the number of turns taken.
+    ///
+    /// # Safety
+    ///
+    /// Uses the shared `VISITED` scratch space, so it must not be called from several threads
+    /// at once, unless the `parallel` feature makes the statics thread-local.
+    ///
+    /// `VISITED` is indexed unchecked: the guard stays within `rows` x `cols` because `start_pos`
+    /// and all obstacles are inside the inner grid, which the private fields guarantee.
     #[allow(static_mut_refs)]
     pub unsafe fn is_looping(&self) -> (bool, usize) {
-        let old_true = TRUE;
+        let visited_len = self.rows * self.cols * 4;
+        if VISITED.len() < visited_len {
+            VISITED.resize(visited_len, 0);
+        }
         TRUE = TRUE.wrapping_add(1);
-        if old_true < TRUE {
+        if TRUE == 0 {
             // wrapped - can't use old values
             VISITED.fill(0);
             TRUE = 1;
@@ -375,20 +422,18 @@ impl Grid {
 
         loop {
             loop_length += 1;
-            let idx = visited_index(i, j, d);
-            if VISITED[idx] == TRUE {
+            let idx = (i * self.cols + j) * 4 + d;
+            if *VISITED.get_unchecked(idx) == TRUE {
                 // Loop detected
                 return (true, loop_length);
             }
-            VISITED[idx] = TRUE;
+            *VISITED.get_unchecked_mut(idx) = TRUE;
 
             let horizontal = d % 2; // 0: vertical, 1: horizontal
             let line_index = (i * horizontal) + (j * (1 - horizontal)); // i if horizontal, j if vertical
 
             // From line, get the next obstacle in the current direction
-            // To do that, shift the bits left or right depending on the direction,
-            // then find the most/least significant bit set (1) depending on the direction:
-            // then count leading/trailing zeros to get the next obstacle in the current direction
+            // To do that, find the next set bit before/after the guard depending on the direction.
             // if no obstacle in the current direction, AND if wall_ahead is false, return false (guard exits the grid)
             // otherwise based on direction, adjust the i/j to just before the next obstacle (or the wall ahead)
             let distance = self.find_next_obstacle_distance(i, j, d);
@@ -416,10 +461,10 @@ impl Grid {
                         i = MIN_POS;
                     }
                     RIGHT => {
-                        j = MAX_POS;
+                        j = self.cols - 1;
                     }
                     DOWN => {
-                        i = MAX_POS;
+                        i = self.rows - 1;
                     }
                     LEFT => {
                         j = MIN_POS;
@@ -435,20 +480,33 @@ impl Grid {
     }
 }
 
-#[aoc(day6, part1)]
+#[cfg_attr(feature = "std", aoc(day6, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day6::part1");
     part1_naive(input)
 }
 
-#[aoc(day6, part2)]
+#[cfg_attr(feature = "std", aoc(day6, part2))]
 pub fn part2(input: &str) -> usize {
-    let mut grid = Grid::parse_input(input);
+    trace_span!("day6::part2");
+    trace_phase!("parse");
+    let padded;
+    let (mut grid, input, on_edge) = match Grid::parse_input(input) {
+        Ok(grid) => (grid, input, false),
+        Err(ParseError::GuardOnEdge { .. }) => {
+            padded = pad_map(input);
+            let grid = Grid::parse_input(&padded).expect("Invalid map");
+            (grid, padded.as_str(), true)
+        }
+        Err(err) => panic!("Invalid map: {err}"),
+    };
+    trace_phase!("walk");
     let (_sth, cells, wall_i, wall_j) = part1_impl(input);
+    trace_phase!("search");
     let mut count = 0;
     for cell in cells {
         let (i, j) = cell;
-        grid.grid[0][j] |= 1 << i;
-        grid.grid[1][i] |= 1 << j;
+        grid.add_obstacle(i, j);
         unsafe {
             let (looping, _) = grid.is_looping();
             if looping {
@@ -456,21 +514,40 @@ pub fn part2(input: &str) -> usize {
                 count += 1;
             }
         }
-        grid.grid[0][j] &= !(1 << i);
-        grid.grid[1][i] &= !(1 << j);
+        grid.remove_obstacle(i, j);
     }
-    // final for wall_i, wall_j
-    grid.walls[wall_i][wall_j] = true;
-    unsafe {
-        if grid.is_looping().0 {
-            // println!("{}, {} - {}", wall_i, wall_j, grid.is_looping().1);
-            count += 1;
+    // final for wall_i, wall_j, unless it is in the padding around the map
+    if !on_edge {
+        grid.walls[wall_i][wall_j] = true;
+        unsafe {
+            if grid.is_looping().0 {
+                // println!("{}, {} - {}", wall_i, wall_j, grid.is_looping().1);
+                count += 1;
+            }
         }
     }
     // println!("Looping: {}", looping);
     count
 }
 
+/// Surrounds the map with a ring of empty cells, so a guard on the edge starts inside the border.
+///
+/// The guard only steps into the ring to leave the map, so the same cells make it loop.
+fn pad_map(input: &str) -> String {
+    let cols = input.lines().next().map_or(0, str::len);
+    let empty = ".".repeat(cols + 2);
+    let mut padded = String::with_capacity((input.lines().count() + 2) * (cols + 3));
+    padded.push_str(&empty);
+    padded.push('\n');
+    for line in input.lines() {
+        padded.push('.');
+        padded.push_str(line);
+        padded.push_str(".\n");
+    }
+    padded.push_str(&empty);
+    padded
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -510,6 +587,155 @@ mod tests {
         assert_eq!(part2(INPUT), 6);
     }
 
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Grid::parse_input("...\n.^.").unwrap_err(),
+            ParseError::TooSmall { rows: 2, cols: 3 }
+        );
+        assert_eq!(
+            Grid::parse_input("...\n.^..\n...").unwrap_err(),
+            ParseError::RaggedRow {
+                row: 1,
+                len: 4,
+                expected: 3
+            }
+        );
+        assert_eq!(
+            Grid::parse_input("...\n...\n...").unwrap_err(),
+            ParseError::NoGuard
+        );
+        assert_eq!(
+            Grid::parse_input("....\n.^^.\n....").unwrap_err(),
+            ParseError::MultipleGuards
+        );
+        assert_eq!(
+            Grid::parse_input("...\n^..\n...").unwrap_err(),
+            ParseError::GuardOnEdge { row: 1, col: 0 }
+        );
+
+        let grid = Grid::parse_input(INPUT).unwrap();
+        assert_eq!((grid.rows, grid.cols, grid.start_pos), (8, 8, (5, 3)));
+        assert!(grid.walls[UP][3] && grid.walls[LEFT][7] && grid.walls[DOWN][5]);
+    }
+
+    #[test]
+    fn test_bit_lines() {
+        let mut lines = BitLines::new(2, 200);
+        for pos in [0, 63, 64, 130, 199] {
+            lines.set(1, pos);
+        }
+        assert_eq!(lines.next_after(1, 0), Some(63));
+        assert_eq!(lines.next_after(1, 64), Some(130));
+        assert_eq!(lines.next_after(1, 199), None);
+        assert_eq!(lines.prev_before(1, 199), Some(130));
+        assert_eq!(lines.prev_before(1, 64), Some(63));
+        assert_eq!(lines.prev_before(1, 0), None);
+        assert_eq!(lines.next_after(0, 0), None);
+        lines.clear(1, 130);
+        assert_eq!(lines.next_after(1, 64), Some(199));
+    }
+
+    #[test]
+    #[should_panic(expected = "past the line end")]
+    fn test_bit_lines_out_of_range() {
+        // Would set a padding bit of line 0 rather than a position on it
+        BitLines::new(2, 200).set(0, 200);
+    }
+
+    /// Part 2 by placing each obstacle and walking cell by cell, `None` if the guard loops anyway.
+    fn part2_brute_force(input: &str) -> Option<usize> {
+        let grid: Vec<&[u8]> = input.lines().map(str::as_bytes).collect();
+        let (rows, cols) = (grid.len() as i32, grid[0].len() as i32);
+        let start = grid.concat().iter().position(|&b| b == b'^').unwrap() as i32;
+        let looping = |extra: Option<(i32, i32)>| {
+            let mut seen = HashSet::new();
+            let (mut r, mut c, mut d) = (start / cols, start % cols, 0);
+            while seen.insert((r, c, d)) {
+                let (dr, dc) = DIRECTIONS[d];
+                let (nr, nc) = (r + dr, c + dc);
+                if nr < 0 || nc < 0 || nr >= rows || nc >= cols {
+                    return false;
+                }
+                if grid[nr as usize][nc as usize] == b'#' || extra == Some((nr, nc)) {
+                    d = (d + 1) % 4;
+                } else {
+                    (r, c) = (nr, nc);
+                }
+            }
+            true
+        };
+        if looping(None) {
+            return None;
+        }
+        let candidates = (0..rows).flat_map(|r| (0..cols).map(move |c| (r, c)));
+        Some(
+            candidates
+                .filter(|&(r, c)| grid[r as usize][c as usize] == b'.' && looping(Some((r, c))))
+                .count(),
+        )
+    }
+
+    #[test]
+    fn test_part2_any_size() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut checked = 0;
+        for (rows, cols) in [
+            (3, 3),
+            (5, 70),
+            (70, 5),
+            (66, 66),
+            (140, 20),
+            (20, 150),
+            (131, 129),
+        ] {
+            for _ in 0..5 {
+                let mut cells: Vec<Vec<u8>> = (0..rows)
+                    .map(|_| {
+                        (0..cols)
+                            .map(|_| if rng.gen_bool(0.08) { b'#' } else { b'.' })
+                            .collect()
+                    })
+                    .collect();
+                cells[rng.gen_range(0..rows)][rng.gen_range(0..cols)] = b'^';
+                let input = cells
+                    .iter()
+                    .map(|row| String::from_utf8(row.clone()).unwrap())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if let Some(expected) = part2_brute_force(&input) {
+                    assert_eq!(part2(&input), expected, "\n{input}");
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_guard_on_edge() {
+        // Blocked at the bottom left corner, walks right along the last row and leaves
+        let input = INPUT.replace('^', ".");
+        let mut lines: Vec<String> = input.lines().map(String::from).collect();
+        lines[9].replace_range(0..1, "^");
+        assert_eq!(part1(&lines.join("\n")), 6);
+
+        let (rows, cols) = (lines.len(), lines[0].len());
+        for (r, c) in (0..rows).flat_map(|r| (0..cols).map(move |c| (r, c))) {
+            let on_edge = r == 0 || c == 0 || r == rows - 1 || c == cols - 1;
+            if !on_edge || input.lines().nth(r).unwrap().as_bytes()[c] != b'.' {
+                continue;
+            }
+            let mut lines: Vec<String> = input.lines().map(String::from).collect();
+            lines[r].replace_range(c..c + 1, "^");
+            let input = lines.join("\n");
+            assert_eq!(Some(part2(&input)), part2_brute_force(&input), "\n{input}");
+        }
+    }
+
     #[test]
     fn test_compare_part1_with_file() {
         let paths = [
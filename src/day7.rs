@@ -5,12 +5,14 @@
 //   backward: if Z ends with digits of Y, Z / (10^len(Y)) = X
 //   To check ending: Z % (10^len(Y)) == Y
 
+use core::hint::unreachable_unchecked;
 use memchr::memchr;
-use std::hint::unreachable_unchecked;
 
 /// Holds target and index of the last number we are currently trying to match.
 /// This is to avoid recursive calls.
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut STACK: [(usize, u64); 100] = [(0, 0); 100];
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut STACK_PTR: usize = 0;
 
 unsafe fn implementation<F>(input: &str, can_form_target_fn: F) -> u64
@@ -23,6 +25,7 @@ where
     let mut numbers = [0u64; 16];
     let mut count: usize;
 
+    trace_phase!("solve");
     while !bytes.is_empty() {
         // Parse target number before ':'
         let (target, offset) = parse_target(&bytes);
@@ -46,13 +49,14 @@ where
     total_sum
 }
 
-#[aoc(day7, part1, naive)]
+#[cfg_attr(feature = "std", aoc(day7, part1, naive))]
 pub fn part1_naive(input: &str) -> u64 {
     part1(input)
 }
 
-#[aoc(day7, part1)]
+#[cfg_attr(feature = "std", aoc(day7, part1))]
 pub fn part1(input: &str) -> u64 {
+    trace_span!("day7::part1");
     unsafe { part1_impl(input) }
 }
 
@@ -62,13 +66,14 @@ unsafe fn part1_impl(input: &str) -> u64 {
     })
 }
 
-#[aoc(day7, part2, naive)]
+#[cfg_attr(feature = "std", aoc(day7, part2, naive))]
 pub fn part2_naive(input: &str) -> u64 {
     part2(input)
 }
 
-#[aoc(day7, part2)]
+#[cfg_attr(feature = "std", aoc(day7, part2))]
 pub fn part2(input: &str) -> u64 {
+    trace_span!("day7::part2");
     unsafe { part2_impl(input) }
 }
 
@@ -1,27 +1,33 @@
-use std::ptr;
-use std::ptr::write_bytes;
-use std::simd::cmp::SimdPartialOrd;
-use std::simd::{u8x64, Simd};
+use core::ptr;
+use core::ptr::write_bytes;
+use core::simd::cmp::SimdPartialOrd;
+use core::simd::{u8x64, Simd};
 
-#[aoc(day8, part1)]
+#[cfg_attr(feature = "std", aoc(day8, part1))]
 pub fn part1(input: &str) -> usize {
+    trace_span!("day8::part1");
     unsafe { part1_impl(input) }
 }
 
-#[aoc(day8, part2)]
+#[cfg_attr(feature = "std", aoc(day8, part2))]
 pub fn part2(input: &str) -> usize {
+    trace_span!("day8::part2");
     unsafe { part2_impl(input) }
 }
 
 /// 75 is enough to cover the range of ASCII 0-9A-Za-z if we subtract b'0' from each character.
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut ANTENNA_COUNTS: [u8; 75] = [0; 75]; // Count of antennas per frequency adjusted to 0-74
-/// Positions of up to 8 antennas per frequency (x, y).
+pub(crate) const MAX_ANTENNAS: usize = 8;
+/// Positions of up to `MAX_ANTENNAS` antennas per frequency (x, y).
 /// Only up to 4 were observed in the input, but having extra space still fits in the cache.
-static mut ANTENNAS: [[(u8, u8); 8]; 75] = [[(0, 0); 8]; 75];
+#[cfg_attr(feature = "parallel", thread_local)]
+static mut ANTENNAS: [[(u8, u8); MAX_ANTENNAS]; 75] = [[(0, 0); MAX_ANTENNAS]; 75];
 /// Bitmask for antinodes at (y, x) - bit = 1 if antinode is present.
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut ANTINODES: [u64; 50] = [0; 50];
 
-const LINE_LEN: usize = 50;
+pub(crate) const LINE_LEN: usize = 50;
 const VALID_MASK: u64 = (1u64 << LINE_LEN) - 1;
 
 /// Return bits that have antennas
@@ -58,6 +64,7 @@ where
     write_bytes(antinodes_ptr, 0, 50);
     let antennas_ptr = ANTENNAS.as_mut_ptr();
 
+    trace_phase!("search");
     for y in 0isize..50isize {
         let mut mask = get_line_mask(line_ptr);
 
@@ -89,6 +96,7 @@ where
         line_ptr = line_ptr.add(51); // Advance to next line for next iteration
     }
 
+    trace_phase!("reduce");
     // Count the bits in ANTINODES
     let mut result = 0usize;
     for i in 0..50 {
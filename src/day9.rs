@@ -1,15 +1,18 @@
-use std::cmp::min;
-use std::simd::{u8x64, Simd};
+use core::cmp::min;
+use core::simd::{u8x64, Simd};
 
-#[aoc(day9, part1)]
-pub fn part1(input: &str) -> usize {
+#[cfg_attr(feature = "std", aoc(day9, part1))]
+pub fn part1(input: &str) -> u64 {
+    trace_span!("day9::part1");
     unsafe { part1_impl(input) }
 }
 
 /// Cumulative positional adjustment term for each file size, e.g. 2 => 0+1, 3 => 0+1+2, 4 => 0+1+2+3, etc.
 const SIZE_TO_POS_ADD: [usize; 10] = [0, 0, 1, 3, 6, 10, 15, 21, 28, 36];
+pub(crate) const MAX_DIGITS: usize = 20_000;
 /// ASCII digits converted to scalar digits
-static mut DIGITS: [u8; 20_000] = [0; 20_000];
+#[cfg_attr(feature = "parallel", thread_local)]
+static mut DIGITS: [u8; MAX_DIGITS] = [0; MAX_DIGITS];
 
 unsafe fn ascii_to_digits_in_place(bytes: &[u8]) {
     let zero = Simd::splat(b'0');
@@ -31,10 +34,13 @@ unsafe fn ascii_to_digits_in_place(bytes: &[u8]) {
 }
 
 #[allow(static_mut_refs)]
-unsafe fn part1_impl(input: &str) -> usize {
+unsafe fn part1_impl(input: &str) -> u64 {
+    trace_phase!("parse");
     ascii_to_digits_in_place(&input.as_bytes()[..input.len() - 1]);
     let digits = &DIGITS[..input.len() - 1];
 
+    trace_phase!("compact");
+
     let mut left = 0;
     let mut give = 0;
 
@@ -50,7 +56,7 @@ unsafe fn part1_impl(input: &str) -> usize {
             // Match found - transfer file pos
             let file_id = right >> 1;
             let pos_mult = pos * size + *SIZE_TO_POS_ADD.get_unchecked(size);
-            checksum += file_id * pos_mult;
+            checksum += file_id as u64 * pos_mult as u64;
             pos += size;
 
             give -= size;
@@ -69,7 +75,7 @@ unsafe fn part1_impl(input: &str) -> usize {
 
             let file_id = left >> 1;
             let pos_mult = pos * size + *SIZE_TO_POS_ADD.get_unchecked(size);
-            checksum += file_id * pos_mult;
+            checksum += file_id as u64 * pos_mult as u64;
             pos += size;
 
             give = *digits.get_unchecked(left + 1) as usize;
@@ -81,18 +87,21 @@ unsafe fn part1_impl(input: &str) -> usize {
     if take > 0 {
         let id = right >> 1;
         let extra = pos * take + *SIZE_TO_POS_ADD.get_unchecked(take);
-        checksum += id * extra;
+        checksum += id as u64 * extra as u64;
     }
 
     checksum
 }
 
 /// Free space positions by size (1-9).
-/// Digits seem to be uniformly distributed, so 2000 per size should be more than enough.
-static mut FREE_HEAPS: [[usize; 2000]; 10] = [[0; 2000]; 10];
+/// Moving a file never adds a free space, so each heap holds at most every free space of the input.
+#[cfg_attr(feature = "parallel", thread_local)]
+static mut FREE_HEAPS: [[usize; MAX_DIGITS / 2]; 10] = [[0; MAX_DIGITS / 2]; 10];
 /// Track the number of free spaces for each size.
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut FREE_SIZES: [usize; 10] = [0; 10];
 /// Track the max free size available.
+#[cfg_attr(feature = "parallel", thread_local)]
 static mut MAX_FREE_SIZE_AVAILABLE: usize = 10;
 
 #[inline(always)]
@@ -223,18 +232,21 @@ unsafe fn heap_cleanup(after_pos: usize) {
     }
 }
 
-pub fn part2(input: &str) -> usize {
+pub fn part2(input: &str) -> u64 {
+    trace_span!("day9::part2");
     unsafe { part2_impl(input) }
 }
 
 #[allow(static_mut_refs)]
-pub unsafe fn part2_impl(input: &str) -> usize {
+pub unsafe fn part2_impl(input: &str) -> u64 {
+    trace_phase!("parse");
     ascii_to_digits_in_place(&input.as_bytes()[..input.len() - 1]);
     let digits = &DIGITS[..input.len() - 1];
     let mut pos = 0;
     let mut checksum = 0;
 
     // Setup heaps of free positions for each size
+    trace_phase!("build tables");
     for i in 0..10 {
         *FREE_SIZES.get_unchecked_mut(i) = 0;
     }
@@ -255,6 +267,7 @@ pub unsafe fn part2_impl(input: &str) -> usize {
     pos += *digits.get_unchecked(digits.len() - 1) as usize;
 
     // Now, traverse backwards to find the best free pos for each file
+    trace_phase!("compact");
     let mut idx = digits.len() - 1;
     while idx > 0 {
         let size = *digits.get_unchecked(idx) as usize;
@@ -264,7 +277,7 @@ pub unsafe fn part2_impl(input: &str) -> usize {
         let (file_pos, free_size) = heap_next_fit(pos, size);
         let file_id = idx >> 1;
         let pos_mult = file_pos * size + *SIZE_TO_POS_ADD.get_unchecked(size);
-        checksum += file_id * pos_mult;
+        checksum += file_id as u64 * pos_mult as u64;
 
         // Readjust the free space if anything was moved
         if free_size > 0 {
@@ -309,7 +322,7 @@ mod tests {
             format!("day{DAY}-alt1.txt"),
             format!("day{DAY}-alt2.txt"),
         ];
-        let outputs: [usize; 3] = [6283404590840, 6607511583593, 6241633730082];
+        let outputs: [u64; 3] = [6283404590840, 6607511583593, 6241633730082];
         for (i, path) in paths.iter().enumerate() {
             let module_dir = Path::new(file!()).parent().unwrap();
             let file_path = module_dir.join(format!("../input/2024/{}", path));
@@ -327,7 +340,7 @@ mod tests {
             format!("day{DAY}-alt1.txt"),
             format!("day{DAY}-alt2.txt"),
         ];
-        let outputs: [usize; 3] = [6304576012713, 6636608781232, 6265268809555];
+        let outputs: [u64; 3] = [6304576012713, 6636608781232, 6265268809555];
         for (i, path) in paths.iter().enumerate() {
             let module_dir = Path::new(file!()).parent().unwrap();
             let file_path = module_dir.join(format!("../input/2024/{}", path));
@@ -0,0 +1,401 @@
+//! Dial's algorithm: a monotone bucket queue for graphs with small integer edge weights.
+//!
+//! This is the general form of the two-queue search in day 16, where every edge costs
+//! either 1 or 1000. Buckets are kept in a ring of `max_weight + 1` slots, so a push
+//! and a pop are both O(1) and the whole search is O(V + E + max_cost).
+//!
+//! States are plain indices in `0..state_count()`. The graph is only ever queried
+//! forwards, predecessors are recorded during the search.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A directed graph over states `0..state_count()` with non-negative integer weights.
+pub trait Graph {
+    fn state_count(&self) -> usize;
+
+    /// Calls `f(next_state, weight)` for every outgoing edge of `state`.
+    /// Weights must not exceed the `max_weight` passed to [`Dial::new`].
+    fn successors<F: FnMut(usize, u32)>(&self, state: usize, f: F);
+}
+
+const UNREACHED: u32 = u32::MAX;
+const NO_PRED: u32 = u32::MAX;
+
+/// Reusable search context. Buffers are kept between searches to avoid reallocating.
+pub struct Dial {
+    max_weight: u32,
+    buckets: Vec<Vec<u32>>,
+    dist: Vec<u32>,
+    // Predecessors are singly linked lists threaded through `pred_edges`.
+    // Each entry is (predecessor state, index of the next entry).
+    pred_head: Vec<u32>,
+    pred_edges: Vec<(u32, u32)>,
+    // The predecessor that first reached each state at its final cost. Zero weight cycles
+    // can loop through `pred_edges`, but these always lead back to a source.
+    tree_pred: Vec<u32>,
+}
+
+impl Dial {
+    pub fn new(max_weight: u32) -> Self {
+        Self {
+            max_weight,
+            buckets: vec![Vec::new(); max_weight as usize + 1],
+            dist: Vec::new(),
+            pred_head: Vec::new(),
+            pred_edges: Vec::new(),
+            tree_pred: Vec::new(),
+        }
+    }
+
+    /// Runs the search to completion from all `sources` (each at cost 0).
+    pub fn search<G: Graph>(&mut self, graph: &G, sources: &[usize]) {
+        self.search_until(graph, sources, |_| false);
+    }
+
+    /// Runs the search from all `sources`, stopping once the cheapest target is settled.
+    /// Every state with the same cost as that target is still settled, so the predecessor
+    /// DAG into any target at that cost is complete.
+    /// Returns the first settled target and its cost.
+    pub fn search_until<G: Graph, T: Fn(usize) -> bool>(
+        &mut self,
+        graph: &G,
+        sources: &[usize],
+        is_target: T,
+    ) -> Option<(usize, u32)> {
+        let n = graph.state_count();
+        debug_assert!(n < NO_PRED as usize);
+        self.reset(n);
+
+        for &s in sources {
+            if self.dist[s] != 0 {
+                self.dist[s] = 0;
+                self.buckets[0].push(s as u32);
+            }
+        }
+
+        let ring = self.buckets.len();
+        let mut pending = self.buckets[0].len();
+        let mut cost = 0u32;
+        let mut found: Option<(usize, u32)> = None;
+
+        while pending > 0 {
+            let slot = cost as usize % ring;
+            while let Some(state) = self.buckets[slot].pop() {
+                pending -= 1;
+                let state = state as usize;
+                // Stale entry - the state was improved after being pushed
+                if self.dist[state] != cost {
+                    continue;
+                }
+                if found.is_none() && is_target(state) {
+                    found = Some((state, cost));
+                }
+
+                graph.successors(state, |next, weight| {
+                    debug_assert!(weight <= self.max_weight);
+                    let new_cost = cost + weight;
+                    let old_cost = self.dist[next];
+                    if new_cost < old_cost {
+                        self.dist[next] = new_cost;
+                        self.pred_head[next] = NO_PRED;
+                        self.tree_pred[next] = state as u32;
+                        self.buckets[new_cost as usize % ring].push(next as u32);
+                        pending += 1;
+                    }
+                    if new_cost <= old_cost {
+                        self.pred_edges.push((state as u32, self.pred_head[next]));
+                        self.pred_head[next] = (self.pred_edges.len() - 1) as u32;
+                    }
+                });
+            }
+
+            // Bucket for `cost` is drained, nothing else can be settled at this cost
+            if found.is_some() {
+                break;
+            }
+            cost += 1;
+        }
+
+        // Drop anything still queued so the next search starts from empty buckets
+        if found.is_some() {
+            self.buckets.iter_mut().for_each(Vec::clear);
+        }
+        found
+    }
+
+    fn reset(&mut self, n: usize) {
+        self.dist.clear();
+        self.dist.resize(n, UNREACHED);
+        self.pred_head.clear();
+        self.pred_head.resize(n, NO_PRED);
+        self.pred_edges.clear();
+        self.tree_pred.clear();
+        self.tree_pred.resize(n, NO_PRED);
+    }
+
+    /// Shortest distance to `state` found by the last search.
+    #[inline(always)]
+    pub fn dist(&self, state: usize) -> Option<u32> {
+        match self.dist[state] {
+            UNREACHED => None,
+            d => Some(d),
+        }
+    }
+
+    /// All predecessors of `state` that lie on some shortest path to it.
+    pub fn predecessors(&self, state: usize) -> Predecessors<'_> {
+        Predecessors {
+            edges: &self.pred_edges,
+            next: self.pred_head[state],
+        }
+    }
+
+    /// One shortest path from a source to `target`, sources first.
+    pub fn path(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist(target)?;
+        let mut path = vec![target];
+        let mut state = target;
+        while self.tree_pred[state] != NO_PRED {
+            state = self.tree_pred[state] as usize;
+            path.push(state);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Marks every state lying on at least one shortest path to any of `targets`.
+    /// This is the backward expansion from day 16 part 2.
+    /// Only targets sharing the cheapest cost among `targets` are expanded.
+    pub fn shortest_path_dag(&self, targets: &[usize]) -> Vec<bool> {
+        let mut on_path = vec![false; self.dist.len()];
+        let best = targets.iter().filter_map(|&t| self.dist(t)).min();
+        let mut stack: Vec<usize> = targets
+            .iter()
+            .copied()
+            .filter(|&t| best.is_some() && self.dist(t) == best)
+            .collect();
+
+        while let Some(state) = stack.pop() {
+            if on_path[state] {
+                continue;
+            }
+            on_path[state] = true;
+            stack.extend(self.predecessors(state).filter(|&p| !on_path[p]));
+        }
+
+        on_path
+    }
+}
+
+pub struct Predecessors<'a> {
+    edges: &'a [(u32, u32)],
+    next: u32,
+}
+
+impl Iterator for Predecessors<'_> {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<usize> {
+        if self.next == NO_PRED {
+            return None;
+        }
+        let (state, next) = self.edges[self.next as usize];
+        self.next = next;
+        Some(state as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Day 16 maze as a state graph: state = cell * 4 + direction.
+    struct Maze {
+        cells: Vec<u8>,
+        width: usize,
+    }
+
+    const DIR: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+    impl Maze {
+        fn new(input: &str) -> Self {
+            let width = input.find('\n').unwrap();
+            let cells = input.bytes().filter(|&b| b != b'\n').collect();
+            Self { cells, width }
+        }
+
+        fn find(&self, c: u8) -> usize {
+            self.cells.iter().position(|&b| b == c).unwrap()
+        }
+
+        fn step(&self, cell: usize, dir: usize) -> usize {
+            let (dx, dy) = DIR[dir];
+            (cell as isize + dx + dy * self.width as isize) as usize
+        }
+    }
+
+    impl Graph for Maze {
+        fn state_count(&self) -> usize {
+            self.cells.len() * 4
+        }
+
+        fn successors<F: FnMut(usize, u32)>(&self, state: usize, mut f: F) {
+            let (cell, dir) = (state / 4, state % 4);
+            let next = self.step(cell, dir);
+            if self.cells[next] != b'#' {
+                f(next * 4 + dir, 1);
+            }
+            f(cell * 4 + (dir + 1) % 4, 1000);
+            f(cell * 4 + (dir + 3) % 4, 1000);
+        }
+    }
+
+    fn solve(input: &str) -> (u32, usize) {
+        let maze = Maze::new(input);
+        let start = maze.find(b'S') * 4;
+        let end = maze.find(b'E');
+        let targets: Vec<usize> = (0..4).map(|d| end * 4 + d).collect();
+
+        let mut dial = Dial::new(1000);
+        let (_, cost) = dial
+            .search_until(&maze, &[start], |s| s / 4 == end)
+            .unwrap();
+        let dag = dial.shortest_path_dag(&targets);
+        let mut cells = vec![false; maze.cells.len()];
+        for (state, _) in dag.iter().enumerate().filter(|(_, &on)| on) {
+            cells[state / 4] = true;
+        }
+        (cost, cells.iter().filter(|&&c| c).count())
+    }
+
+    const INPUT: &str = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+";
+
+    const INPUT2: &str = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################
+";
+
+    /// Explicit edge list, weights given per edge.
+    struct Edges(usize, Vec<(usize, usize, u32)>);
+
+    impl Graph for Edges {
+        fn state_count(&self) -> usize {
+            self.0
+        }
+
+        fn successors<F: FnMut(usize, u32)>(&self, state: usize, mut f: F) {
+            for &(from, to, w) in &self.1 {
+                if from == state {
+                    f(to, w);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_day16_examples() {
+        assert_eq!(solve(INPUT), (7036, 45));
+        assert_eq!(solve(INPUT2), (11048, 64));
+    }
+
+    #[test]
+    fn test_reuse_context() {
+        let mut dial = Dial::new(1000);
+        for input in [INPUT, INPUT2, INPUT] {
+            let maze = Maze::new(input);
+            let start = maze.find(b'S') * 4;
+            let end = maze.find(b'E');
+            dial.search(&maze, &[start]);
+            let best = (0..4).filter_map(|d| dial.dist(end * 4 + d)).min();
+            assert_eq!(best, Some(solve(input).0));
+        }
+    }
+
+    #[test]
+    fn test_predecessors_and_path() {
+        // Two equal paths 0->1->3 and 0->2->3, plus a longer direct edge
+        let graph = Edges(
+            5,
+            vec![(0, 1, 2), (0, 2, 1), (1, 3, 1), (2, 3, 2), (0, 3, 4)],
+        );
+        let mut dial = Dial::new(4);
+        dial.search(&graph, &[0]);
+
+        assert_eq!(dial.dist(3), Some(3));
+        assert_eq!(dial.dist(4), None);
+        let mut preds: Vec<usize> = dial.predecessors(3).collect();
+        preds.sort_unstable();
+        assert_eq!(preds, vec![1, 2]);
+
+        let path = dial.path(3).unwrap();
+        assert_eq!(path.first(), Some(&0));
+        assert_eq!(path.last(), Some(&3));
+        assert_eq!(path.len(), 3);
+        assert_eq!(dial.path(4), None);
+
+        let dag = dial.shortest_path_dag(&[3]);
+        assert_eq!(dag, vec![true, true, true, true, false]);
+    }
+
+    #[test]
+    fn test_zero_weights_and_multiple_sources() {
+        let graph = Edges(
+            5,
+            vec![(0, 1, 0), (1, 2, 0), (3, 2, 1), (2, 4, 3), (0, 4, 3)],
+        );
+        let mut dial = Dial::new(3);
+        let found = dial.search_until(&graph, &[0, 3], |s| s == 4);
+        assert_eq!(found, Some((4, 3)));
+        assert_eq!(dial.dist(2), Some(0));
+        let dag = dial.shortest_path_dag(&[4]);
+        assert_eq!(dag, vec![true, true, true, false, true]);
+    }
+
+    #[test]
+    fn test_path_with_zero_weight_cycle() {
+        // 0 <-> 1 at no cost, and a zero weight edge back into the source
+        let graph = Edges(
+            4,
+            vec![(0, 1, 0), (1, 0, 0), (1, 2, 1), (2, 1, 0), (2, 3, 1)],
+        );
+        let mut dial = Dial::new(1);
+        dial.search(&graph, &[0]);
+
+        assert_eq!(dial.path(0), Some(vec![0]));
+        assert_eq!(dial.path(1), Some(vec![0, 1]));
+        assert_eq!(dial.path(3), Some(vec![0, 1, 2, 3]));
+        assert!(dial.predecessors(0).any(|p| p == 1));
+        assert_eq!(dial.shortest_path_dag(&[3]), vec![true, true, true, true]);
+    }
+}
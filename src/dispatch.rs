@@ -0,0 +1,59 @@
+//! Runtime dispatch from a `(day, part)` pair to the fast solver for that puzzle.
+
+use crate::input::normalize;
+use crate::*;
+use alloc::string::{String, ToString};
+
+macro_rules! dispatch {
+    ($day:expr, $part:expr, $input:expr; $($d:literal => $module:ident),*) => {
+        match ($day, $part) {
+            $(
+                ($d, 1) => Some($module::part1($input).to_string()),
+                ($d, 2) => Some($module::part2($input).to_string()),
+            )*
+            _ => None,
+        }
+    };
+}
+
+/// Runs the solver for `day` and `part` on the normalized input and formats its answer.
+/// Returns `None` if there is no such puzzle.
+pub fn solve(day: u32, part: u32, input: &str) -> Option<String> {
+    solve_normalized(day, part, &normalize(input))
+}
+
+/// [`solve`] for input that has already been through [`normalize`].
+pub fn solve_normalized(day: u32, part: u32, input: &str) -> Option<String> {
+    dispatch!(day, part, input;
+        1 => day1, 2 => day2, 3 => day3, 4 => day4, 5 => day5,
+        6 => day6, 7 => day7, 8 => day8, 9 => day9, 10 => day10,
+        11 => day11, 12 => day12, 13 => day13, 14 => day14, 15 => day15,
+        16 => day16, 17 => day17, 18 => day18, 19 => day19, 20 => day20,
+        21 => day21, 22 => day22, 23 => day23, 24 => day24, 25 => day25
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(
+            solve(9, 1, "2333133121414131402\n").as_deref(),
+            Some("1928")
+        );
+        assert_eq!(
+            solve(9, 2, "2333133121414131402\n").as_deref(),
+            Some("2858")
+        );
+        assert_eq!(solve(25, 2, "").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn test_unknown_puzzle() {
+        assert_eq!(solve(0, 1, ""), None);
+        assert_eq!(solve(26, 1, ""), None);
+        assert_eq!(solve(1, 3, ""), None);
+    }
+}
@@ -0,0 +1,301 @@
+//! Example fixtures extracted from saved puzzle pages.
+//!
+//! A page's example inputs are the `<pre><code>` blocks introduced by a paragraph that
+//! mentions an example; other blocks, including those walking through an example "above",
+//! are diagrams. The answer for each part is the last
+//! `<code><em>` in that part's `<article>`, attached to the nearest example before it.
+//! This matches most pages but not all, so review `expected.txt` after extracting.
+//!
+//! Fixtures live in `examples/dayN/`: one `exampleK.txt` per input and an `expected.txt`
+//! with `<file> <part> <answer>` lines.
+
+use crate::*;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const EXPECTED_FILE: &str = "expected.txt";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Example {
+    pub input: String,
+    /// Expected answers for part 1 and part 2, if the page gives them.
+    pub answers: [Option<String>; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fixture {
+    pub day: u32,
+    pub part: u32,
+    pub name: String,
+    pub input: String,
+    pub expected: String,
+}
+
+/// Extracts example inputs and their answers from a puzzle page.
+pub fn extract(html: &str) -> Vec<Example> {
+    let mut articles = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = html[rest..].find("<article") {
+        let start = rest + start;
+        let end = html[start..]
+            .find("</article>")
+            .map_or(html.len(), |end| start + end);
+        articles.push((start, end));
+        rest = end;
+    }
+    if articles.is_empty() {
+        articles.push((0, html.len()));
+    }
+
+    let blocks = find_all(html, "<pre><code>", "</code></pre>");
+    let mut examples: Vec<(usize, Example)> = Vec::new();
+    for &(start, content) in &blocks {
+        let paragraph = &html[html[..start].rfind("<p>").unwrap_or(0)..start];
+        let paragraph = paragraph.to_lowercase();
+        if paragraph.contains("example") && !paragraph.contains("above") {
+            let input = unescape(content);
+            if !examples.iter().any(|(_, e)| e.input == input) {
+                examples.push((
+                    start,
+                    Example {
+                        input,
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+    }
+    if examples.is_empty() {
+        for &(start, content) in &blocks {
+            examples.push((
+                start,
+                Example {
+                    input: unescape(content),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+
+    for (part, &(start, end)) in articles.iter().take(2).enumerate() {
+        let answers = find_all(&html[..end], "<code><em>", "</em></code>");
+        let Some(&(pos, answer)) = answers.iter().rev().find(|&&(pos, _)| pos >= start) else {
+            continue;
+        };
+        if let Some((_, example)) = examples.iter_mut().rev().find(|(s, _)| *s < pos) {
+            example.answers[part] = Some(unescape(answer));
+        }
+    }
+
+    examples.into_iter().map(|(_, e)| e).collect()
+}
+
+/// Finds every `open ... close` span, returning the offset of `open` and the text in between.
+fn find_all<'a>(html: &'a str, open: &str, close: &str) -> Vec<(usize, &'a str)> {
+    let mut spans = Vec::new();
+    let mut rest = 0;
+    while let Some(start) = html[rest..].find(open) {
+        let start = rest + start;
+        let content_start = start + open.len();
+        let Some(len) = html[content_start..].find(close) else {
+            break;
+        };
+        spans.push((start, &html[content_start..content_start + len]));
+        rest = content_start + len + close.len();
+    }
+    spans
+}
+
+/// Drops nested tags (e.g. `<em>` inside examples) and decodes the entities AoC pages use.
+fn unescape(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Writes `examples` to `dir` as `exampleK.txt` files plus `expected.txt`.
+pub fn write_fixtures(dir: &Path, examples: &[Example]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut expected = String::new();
+    for (i, example) in examples.iter().enumerate() {
+        let name = format!("example{}.txt", i + 1);
+        let mut input = example.input.clone();
+        if !input.ends_with('\n') {
+            input.push('\n');
+        }
+        fs::write(dir.join(&name), input)?;
+        for (part, answer) in example.answers.iter().enumerate() {
+            if let Some(answer) = answer {
+                expected += &format!("{} {} {}\n", name, part + 1, answer);
+            }
+        }
+    }
+    fs::write(dir.join(EXPECTED_FILE), expected)
+}
+
+/// Loads every fixture under `root` (`root/dayN/expected.txt`), sorted by day.
+pub fn load_fixtures(root: &Path) -> io::Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let dir = entry?.path();
+        let day = match dir
+            .file_name()
+            .and_then(|name| name.to_str()?.strip_prefix("day")?.parse().ok())
+        {
+            Some(day) => day,
+            None => continue,
+        };
+        let expected = match fs::read_to_string(dir.join(EXPECTED_FILE)) {
+            Ok(expected) => expected,
+            Err(_) => continue,
+        };
+        for line in expected.lines().filter(|line| !line.trim().is_empty()) {
+            let mut fields = line.splitn(3, ' ');
+            let (Some(name), Some(part), Some(answer)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: bad line {:?}", dir.display(), line),
+                ));
+            };
+            fixtures.push(Fixture {
+                day,
+                part: part.parse().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("bad part in {:?}", line),
+                    )
+                })?,
+                name: name.to_string(),
+                input: fs::read_to_string(dir.join(name))?,
+                expected: answer.to_string(),
+            });
+        }
+    }
+    fixtures.sort_by(|a, b| (a.day, a.part, &a.name).cmp(&(b.day, b.part, &b.name)));
+    Ok(fixtures)
+}
+
+macro_rules! example_solvers {
+    ($day:expr, $part:expr; $($d:literal, $p:literal => $solver:path),* $(,)?) => {
+        match ($day, $part) {
+            $(($d, $p) => Some(|input: &str| $solver(input).to_string()),)*
+            _ => None,
+        }
+    };
+}
+
+/// A registered solver for `day` and `part` that accepts inputs of any size, which examples need.
+/// Most fast solvers assume the exact size of the real input, so days without a general solver
+/// (e.g. the fixed grids of day 4 and day 16, or day 19's fixed pattern count) return `None`.
+pub fn example_solver(day: u32, part: u32) -> Option<fn(&str) -> String> {
+    example_solvers!(day, part;
+        1, 1 => day1::part1, 1, 2 => day1::part2,
+        2, 1 => day2::part1, 2, 2 => day2::part2,
+        3, 1 => day3::part1_naive, 3, 2 => day3::part2_naive,
+        5, 1 => day5::part1, 5, 2 => day5::part2,
+        6, 1 => day6::part1, 6, 2 => day6::part2,
+        7, 1 => day7::part1, 7, 2 => day7::part2,
+        9, 1 => day9::part1, 9, 2 => day9::part2,
+        23, 1 => day23::part1, 23, 2 => day23::part2,
+        24, 1 => day24::part1,
+        25, 1 => day25::part1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Trimmed from the day 9 page: one example, diagrams, and both answers
+    const PAGE: &str = r#"<main>
+<article class="day-desc"><h2>--- Day 9: Disk Fragmenter ---</h2>
+<p>For example, suppose you have the following disk map:</p>
+<pre><code>2333133121414131402</code></pre>
+<p>The first example above would be represented like this:</p>
+<pre><code>00...111...2...333.44.5555.6666.777.888899</code></pre>
+<p>Continuing the first example, the first few blocks' position multiplied by its file ID number are <code>0 * 0 = 0</code>, <code>1 * 0 = 0</code>; in this example, the checksum is <code><em>1928</em></code>.</p>
+</article>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>The first example from above now proceeds differently:</p>
+<pre><code>00...111...2...333.44.5555.6666.777.888899
+0099.111...2...333.44.5555.6666.777.8888..</code></pre>
+<p>In this example, the checksum would be <code><em>2858</em></code>.</p>
+</article>
+</main>"#;
+
+    #[test]
+    fn test_extract() {
+        let examples = extract(PAGE);
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].input, "2333133121414131402");
+        assert_eq!(
+            examples[0].answers,
+            [Some("1928".to_string()), Some("2858".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(
+            unescape("xmul(2,4)&amp;mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)un<em>do()</em>?mul(8,5))&lt;&gt;"),
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))<>"
+        );
+    }
+
+    #[test]
+    fn test_fixture_round_trip() {
+        let root = std::env::temp_dir().join(format!("aoc_examples_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let examples = extract(PAGE);
+        write_fixtures(&root.join("day9"), &examples).unwrap();
+
+        let fixtures = load_fixtures(&root).unwrap();
+        assert_eq!(fixtures.len(), 2);
+        for fixture in &fixtures {
+            assert_eq!(fixture.input, "2333133121414131402\n");
+            let solver = example_solver(fixture.day, fixture.part).unwrap();
+            assert_eq!(solver(&fixture.input), fixture.expected);
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_fixtures() {
+        let module_dir = Path::new(file!()).parent().unwrap();
+        let Ok(fixtures) = load_fixtures(&module_dir.join("../examples")) else {
+            return;
+        };
+        for fixture in fixtures {
+            let Some(solver) = example_solver(fixture.day, fixture.part) else {
+                println!(
+                    "No example solver for day {} part {}",
+                    fixture.day, fixture.part
+                );
+                continue;
+            };
+            assert_eq!(
+                solver(&fixture.input),
+                fixture.expected,
+                "day{}/{} part {}",
+                fixture.day,
+                fixture.name,
+                fixture.part
+            );
+        }
+    }
+}
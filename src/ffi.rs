@@ -0,0 +1,197 @@
+//! C ABI for embedding the solvers, enabled with the `ffi` cargo feature.
+//!
+//! Build the shared library with
+//! `cargo rustc --release --lib --features ffi --crate-type cdylib`
+//! and include `include/aoc.h`, which is generated from [`c_header`]. The crate type is passed on
+//! the command line because declaring a cdylib in `Cargo.toml` breaks native `no_std` builds.
+//!
+//! Input is rejected with `AOC_ERR_INVALID_INPUT` unless it has the layout the fast solvers read
+//! without bounds checks, see [`fits_layout`].
+//!
+//! Solvers reuse static scratch buffers, so calls must not overlap across threads.
+
+use crate::dispatch;
+use crate::input::{fits_layout, normalize};
+use std::panic;
+use std::slice;
+
+pub const AOC_ERR_NULL_POINTER: i32 = -1;
+pub const AOC_ERR_UNKNOWN_PUZZLE: i32 = -2;
+pub const AOC_ERR_INVALID_INPUT: i32 = -3;
+pub const AOC_ERR_BUFFER_TOO_SMALL: i32 = -4;
+pub const AOC_ERR_PANIC: i32 = -5;
+
+const YEAR: u32 = 2024;
+
+/// Solves `part` of `day` for `input` and writes the answer to `out_buf` as a NUL-terminated
+/// string. Returns the answer length without the NUL, or a negative `AOC_ERR_*` code.
+///
+/// # Safety
+/// `input_ptr` must point to `input_len` readable bytes and `out_buf` to `out_cap` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(
+    year: u32,
+    day: u32,
+    part: u32,
+    input_ptr: *const u8,
+    input_len: usize,
+    out_buf: *mut u8,
+    out_cap: usize,
+) -> i32 {
+    if input_ptr.is_null() || out_buf.is_null() {
+        return AOC_ERR_NULL_POINTER;
+    }
+    if year != YEAR || !(1..=25).contains(&day) || !(1..=2).contains(&part) {
+        return AOC_ERR_UNKNOWN_PUZZLE;
+    }
+    let Ok(input) = std::str::from_utf8(slice::from_raw_parts(input_ptr, input_len)) else {
+        return AOC_ERR_INVALID_INPUT;
+    };
+    let input = normalize(input);
+    if input.is_empty() || !fits_layout(day, &input) {
+        return AOC_ERR_INVALID_INPUT;
+    }
+
+    let answer = match panic::catch_unwind(|| dispatch::solve_normalized(day, part, &input)) {
+        Ok(Some(answer)) => answer,
+        Ok(None) => return AOC_ERR_UNKNOWN_PUZZLE,
+        Err(_) => return AOC_ERR_PANIC,
+    };
+    if answer.len() >= out_cap {
+        return AOC_ERR_BUFFER_TOO_SMALL;
+    }
+
+    let out = slice::from_raw_parts_mut(out_buf, out_cap);
+    out[..answer.len()].copy_from_slice(answer.as_bytes());
+    out[answer.len()] = 0;
+    answer.len() as i32
+}
+
+/// Contents of `include/aoc.h`.
+pub fn c_header() -> String {
+    let codes = [
+        ("AOC_ERR_NULL_POINTER", AOC_ERR_NULL_POINTER),
+        ("AOC_ERR_UNKNOWN_PUZZLE", AOC_ERR_UNKNOWN_PUZZLE),
+        ("AOC_ERR_INVALID_INPUT", AOC_ERR_INVALID_INPUT),
+        ("AOC_ERR_BUFFER_TOO_SMALL", AOC_ERR_BUFFER_TOO_SMALL),
+        ("AOC_ERR_PANIC", AOC_ERR_PANIC),
+    ];
+    let mut header = String::from(
+        "/* Generated from src/ffi.rs, do not edit. */\n\
+         #ifndef AOC_H\n\
+         #define AOC_H\n\
+         \n\
+         #include <stddef.h>\n\
+         #include <stdint.h>\n\
+         \n",
+    );
+    for (name, code) in codes {
+        header += &format!("#define {} ({})\n", name, code);
+    }
+    header += "\n\
+        #ifdef __cplusplus\n\
+        extern \"C\" {\n\
+        #endif\n\
+        \n\
+        /* Solves `part` of `day` for `input` and writes the answer to `out_buf` as a\n\
+        \x20* NUL-terminated string. Returns the answer length without the NUL, or a negative\n\
+        \x20* AOC_ERR_* code. Not thread-safe. */\n\
+        int32_t aoc_solve(uint32_t year, uint32_t day, uint32_t part,\n\
+        \x20                 const uint8_t *input_ptr, size_t input_len,\n\
+        \x20                 uint8_t *out_buf, size_t out_cap);\n\
+        \n\
+        #ifdef __cplusplus\n\
+        }\n\
+        #endif\n\
+        \n\
+        #endif /* AOC_H */\n";
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    const INPUT: &str = "2333133121414131402\n";
+
+    fn solve(year: u32, day: u32, part: u32, input: &str, out: &mut [u8]) -> i32 {
+        unsafe {
+            aoc_solve(
+                year,
+                day,
+                part,
+                input.as_ptr(),
+                input.len(),
+                out.as_mut_ptr(),
+                out.len(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut out = [0xFF; 16];
+        assert_eq!(solve(2024, 9, 1, INPUT, &mut out), 4);
+        assert_eq!(&out[..5], b"1928\0");
+        assert_eq!(solve(2024, 9, 2, INPUT, &mut out), 4);
+        assert_eq!(&out[..5], b"2858\0");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut out = [0; 16];
+        assert_eq!(solve(2023, 9, 1, INPUT, &mut out), AOC_ERR_UNKNOWN_PUZZLE);
+        assert_eq!(solve(2024, 26, 1, INPUT, &mut out), AOC_ERR_UNKNOWN_PUZZLE);
+        assert_eq!(solve(2024, 9, 3, INPUT, &mut out), AOC_ERR_UNKNOWN_PUZZLE);
+        assert_eq!(solve(2024, 9, 1, "", &mut out), AOC_ERR_INVALID_INPUT);
+        assert_eq!(
+            solve(2024, 9, 1, INPUT, &mut out[..4]),
+            AOC_ERR_BUFFER_TOO_SMALL
+        );
+        let invalid_utf8 = [0xC3, 0x28];
+        let result =
+            unsafe { aoc_solve(2024, 9, 1, invalid_utf8.as_ptr(), 2, out.as_mut_ptr(), 16) };
+        assert_eq!(result, AOC_ERR_INVALID_INPUT);
+        let result = unsafe { aoc_solve(2024, 9, 1, std::ptr::null(), 0, out.as_mut_ptr(), 16) };
+        assert_eq!(result, AOC_ERR_NULL_POINTER);
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let mut out = [0; 16];
+        // An even number of digits ends on a free space instead of a file
+        assert_eq!(
+            solve(2024, 9, 1, "23331331214141314022\n", &mut out),
+            AOC_ERR_INVALID_INPUT
+        );
+        assert_eq!(
+            solve(2024, 9, 1, "2333a\n", &mut out),
+            AOC_ERR_INVALID_INPUT
+        );
+        // Day 1 falls back to its general parser instead
+        assert_eq!(solve(2024, 1, 1, "  123   00001\n", &mut out), 3);
+        for day in [4, 8, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23] {
+            for input in ["1\n", "  123   00001\n", "###\n#S#\n###\n"] {
+                assert_eq!(
+                    solve(2024, day, 1, input, &mut out),
+                    AOC_ERR_INVALID_INPUT,
+                    "day {day} accepted {input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_header_up_to_date() {
+        let module_dir = Path::new(file!()).parent().unwrap();
+        let header_path = module_dir.join("../include/aoc.h");
+        let header = fs::read_to_string(header_path).expect("Failed to read the header");
+        assert_eq!(
+            header,
+            c_header(),
+            "include/aoc.h is stale, regenerate it from c_header()"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Input normalization for the fast parsers, which assume exact byte layouts:
+//! LF line endings, no BOM and exactly one trailing newline.
+//!
+//! Normalization alone does not make arbitrary text safe to hand to the fast parsers,
+//! most of which read at fixed offsets without bounds checks. [`fits_layout`] checks the
+//! rest of the layout each of them assumes.
+
+use crate::{
+    day10, day12, day13, day14, day15, day16, day18, day19, day20, day22, day4, day8, day9,
+};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use core::ops::RangeInclusive;
+use memchr::memchr;
+
+const BOM: char = '\u{feff}';
+
+/// Strips a leading BOM, converts CRLF to LF and leaves exactly one trailing newline.
+/// Borrows when no bytes need to change apart from dropping the BOM. Empty input stays empty.
+pub fn normalize(input: &str) -> Cow<'_, str> {
+    let input = input.strip_prefix(BOM).unwrap_or(input);
+    let content = input.trim_end_matches(['\r', '\n']);
+    if content.is_empty() {
+        return Cow::Borrowed("");
+    }
+
+    let has_cr = memchr(b'\r', content.as_bytes()).is_some();
+    if !has_cr && input.len() == content.len() + 1 {
+        // Already ends with a single '\n'
+        return Cow::Borrowed(input);
+    }
+
+    let mut normalized = String::with_capacity(content.len() + 1);
+    if has_cr {
+        let mut rest = content;
+        while let Some(i) = rest.find("\r\n") {
+            normalized.push_str(&rest[..i]);
+            normalized.push('\n');
+            rest = &rest[i + 2..];
+        }
+        normalized.push_str(rest);
+    } else {
+        normalized.push_str(content);
+    }
+    normalized.push('\n');
+    Cow::Owned(normalized)
+}
+
+/// Whether normalized `input` has the layout the fast solver for `day` reads without bounds
+/// checks. Inputs that pass can still be unsolvable, e.g. a maze without a path, but no longer
+/// make a solver read or write out of bounds.
+///
+/// Days whose parsers are bounds-checked accept any input: they panic on malformed input instead.
+pub fn fits_layout(day: u32, input: &str) -> bool {
+    match day {
+        4 => is_grid(input, day4::GRID_SIZE, day4::GRID_SIZE, |_| true),
+        5 => input.split_once("\n\n").is_some_and(|(rules, updates)| {
+            rules.lines().all(|rule| {
+                rule.split_once('|')
+                    .is_some_and(|(a, b)| is_number(a, 2..=2) && is_number(b, 2..=2))
+            }) && updates.lines().all(|update| {
+                update.split(',').count() >= 2
+                    && update.split(',').all(|page| is_number(page, 2..=2))
+            })
+        }),
+        7 => input.lines().all(|line| {
+            line.split_once(": ").is_some_and(|(target, numbers)| {
+                is_number(target, 1..=20) && numbers.split(' ').all(|n| is_number(n, 1..=3))
+            })
+        }),
+        8 => {
+            let mut antennas = [0; 75];
+            is_grid(input, day8::LINE_LEN, day8::LINE_LEN, |b| {
+                b == b'.' || b.is_ascii_alphanumeric()
+            }) && input.bytes().filter(u8::is_ascii_alphanumeric).all(|b| {
+                antennas[(b - b'0') as usize] += 1;
+                antennas[(b - b'0') as usize] <= day8::MAX_ANTENNAS
+            })
+        }
+        9 => {
+            let digits = input.strip_suffix('\n').unwrap_or(input);
+            digits.len() % 2 == 1 && is_number(digits, 1..=day9::MAX_DIGITS)
+        }
+        10 => {
+            let cols = input.find('\n').unwrap_or(0);
+            input.len() <= day10::MEMO_SIZE
+                && is_grid(input, input.len() / (cols + 1), cols, |b| {
+                    b == b'.' || b.is_ascii_digit()
+                })
+        }
+        12 => {
+            let size = input.find('\n').unwrap_or(0);
+            size <= day12::MAX_SIZE && is_grid(input, size, size, |b| b.is_ascii_uppercase())
+        }
+        13 => {
+            input.split("\n\n").count() == day13::NUM_EQUATIONS
+                && input.split("\n\n").all(is_claw_machine)
+        }
+        14 => {
+            input.lines().count() == day14::NUM_ROBOTS
+                && input.lines().all(|line| {
+                    let Some((p, v)) = line.strip_prefix("p=").and_then(|l| l.split_once(" v="))
+                    else {
+                        return false;
+                    };
+                    is_pair(p, 1..=3, |n| n)
+                        && is_pair(v, 1..=2, |n| n.strip_prefix('-').unwrap_or(n))
+                })
+        }
+        15 => input.split_once("\n\n").is_some_and(|(grid, moves)| {
+            let size = day15::GRID_LINES;
+            is_grid(&input[..grid.len() + 1], size, size, |b| {
+                b"#.O@".contains(&b)
+            }) && has_wall_border(grid, size)
+                && grid.bytes().filter(|&b| b == b'@').count() == 1
+                && is_grid(
+                    moves,
+                    day15::INSTRUCTION_LINES,
+                    day15::INSTRUCTIONS_PER_LINE,
+                    |b| b"<>^v".contains(&b),
+                )
+        }),
+        16 => {
+            let size = day16::GRID_SIZE;
+            let line_len = size + 1;
+            is_grid(input, size, size, |b| b"#.SE".contains(&b))
+                && has_wall_border(input, size)
+                && input.as_bytes()[(size - 2) * line_len + 1] == b'S'
+                && input.as_bytes()[line_len + size - 2] == b'E'
+        }
+        17 => {
+            let program = input
+                .strip_prefix("Register A: ")
+                .and_then(|rest| rest.split_once("\nRegister B: 0\nRegister C: 0\n\nProgram: "));
+            program.is_some_and(|(a, program)| {
+                let program = program.as_bytes();
+                is_number(a, 8..=8)
+                    && program.len() == 32
+                    && program[..31]
+                        .iter()
+                        .step_by(2)
+                        .all(|b| (b'0'..=b'7').contains(b))
+                    && program[1..31].iter().step_by(2).all(|&b| b == b',')
+                    && program.starts_with(b"2,4,1,")
+                    && program[12..31].iter().step_by(4).any(|&b| b == b'1')
+            })
+        }
+        18 => {
+            let coords =
+                |n: &str| is_number(n, 1..=2) && n.parse::<usize>().unwrap() < day18::GRID_SIZE;
+            input.lines().count() >= day18::P2_START_FROM_BLOCKS
+                && input.lines().all(|line| {
+                    line.split_once(',')
+                        .is_some_and(|(x, y)| coords(x) && coords(y))
+                })
+        }
+        19 => input.split_once("\n\n").is_some_and(|(patterns, designs)| {
+            let is_towel = |s: &str| !s.is_empty() && s.bytes().all(|b| b"wubrg".contains(&b));
+            let nodes = patterns.len() - patterns.matches(", ").count() * 2;
+            patterns.split(", ").count() == day19::TOTAL_PATTERNS
+                && patterns.split(", ").all(is_towel)
+                && (nodes + 1) * day19::NODE_SIZE <= day19::TRIE_SIZE
+                && designs
+                    .lines()
+                    .all(|design| is_towel(design) && design.len() < day19::COUNTS_SIZE)
+        }),
+        20 => {
+            let size = day20::GRID_SIZE;
+            is_grid(input, size, size, |b| b"#.SE".contains(&b))
+                && has_wall_border(input, size)
+                && input.bytes().filter(|&b| b == b'S').count() == 1
+                && input.bytes().filter(|&b| b == b'E').count() == 1
+        }
+        21 => {
+            input.lines().count() == 5
+                && input
+                    .lines()
+                    .all(|code| code.strip_suffix('A').is_some_and(|n| is_number(n, 3..=3)))
+        }
+        22 => input.lines().all(|secret| {
+            is_number(secret, 6..=8) && secret.parse::<u32>().unwrap() < day22::MODULO
+        }),
+        23 => input.lines().all(|line| {
+            let line = line.as_bytes();
+            line.len() == 5
+                && line[2] == b'-'
+                && [0, 1, 3, 4].iter().all(|&i| line[i].is_ascii_lowercase())
+        }),
+        _ => true,
+    }
+}
+
+/// Whether `input` is `rows` lines of `cols` bytes, each accepted by `cell`.
+fn is_grid(input: &str, rows: usize, cols: usize, cell: impl Fn(u8) -> bool) -> bool {
+    input.len() == rows * (cols + 1)
+        && input
+            .as_bytes()
+            .chunks(cols + 1)
+            .all(|line| line[cols] == b'\n' && line[..cols].iter().all(|&b| cell(b)))
+}
+
+/// Whether the outermost rows and columns of a square grid are all walls.
+fn has_wall_border(grid: &str, size: usize) -> bool {
+    grid.lines().enumerate().all(|(row, line)| {
+        let line = line.as_bytes();
+        if row == 0 || row == size - 1 {
+            line.iter().all(|&b| b == b'#')
+        } else {
+            line[0] == b'#' && line[size - 1] == b'#'
+        }
+    })
+}
+
+fn is_number(s: &str, digits: RangeInclusive<usize>) -> bool {
+    digits.contains(&s.len()) && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Whether `s` is two comma-separated numbers of `digits` digits after `strip`.
+fn is_pair(s: &str, digits: RangeInclusive<usize>, strip: impl Fn(&str) -> &str) -> bool {
+    s.split_once(',')
+        .is_some_and(|(a, b)| is_number(strip(a), digits.clone()) && is_number(strip(b), digits))
+}
+
+/// Whether `block` is a day 13 claw machine with two-digit buttons and a prize of 3 to 8 digits.
+fn is_claw_machine(block: &str) -> bool {
+    let mut lines = block.lines();
+    let button = |line: Option<&str>, name: &str| {
+        line.and_then(|l| l.strip_prefix(name))
+            .and_then(|l| l.strip_prefix(": X+"))
+            .and_then(|l| l.split_once(", Y+"))
+            .is_some_and(|(x, y)| is_number(x, 2..=2) && is_number(y, 2..=2))
+    };
+    button(lines.next(), "Button A")
+        && button(lines.next(), "Button B")
+        && lines
+            .next()
+            .and_then(|l| l.strip_prefix("Prize: X="))
+            .and_then(|l| l.split_once(", Y="))
+            .is_some_and(|(x, y)| is_number(x, 3..=8) && is_number(y, 3..=8))
+        && lines.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_input_is_borrowed() {
+        let input = "1 2\n3 4\n";
+        assert!(matches!(normalize(input), Cow::Borrowed(s) if s == input));
+        assert!(matches!(normalize("\u{feff}1 2\n"), Cow::Borrowed("1 2\n")));
+        assert!(matches!(normalize(""), Cow::Borrowed("")));
+        assert!(matches!(normalize("\r\n\n"), Cow::Borrowed("")));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("1 2\r\n3 4\r\n"), "1 2\n3 4\n");
+        assert_eq!(normalize("\u{feff}1 2\r\n3 4"), "1 2\n3 4\n");
+        assert_eq!(normalize("1 2\n3 4"), "1 2\n3 4\n");
+        assert_eq!(normalize("1 2\n3 4\n\n\n"), "1 2\n3 4\n");
+        assert_eq!(normalize("a\r\n\r\nb\r\n"), "a\n\nb\n");
+        // Lone carriage returns are not line endings
+        assert_eq!(normalize("a\rb\r\n"), "a\rb\n");
+    }
+
+    #[test]
+    fn test_solvers_accept_crlf() {
+        let input = "2333133121414131402\r\n\r\n";
+        assert_eq!(crate::dispatch::solve(9, 1, input).as_deref(), Some("1928"));
+    }
+}
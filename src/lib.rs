@@ -1,9 +1,22 @@
 #![feature(portable_simd)]
+#![cfg_attr(feature = "parallel", feature(thread_local))]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#[cfg(feature = "std")]
 extern crate aoc_runner;
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate aoc_runner_derive;
+extern crate alloc;
 extern crate core;
 
+#[macro_use]
+pub mod trace;
+
+pub mod answer;
+#[cfg(feature = "client")]
+pub mod aoc_client;
+#[cfg(feature = "parallel")]
+pub mod batch;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -29,5 +42,18 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod dial;
+pub mod dispatch;
+#[cfg(feature = "std")]
+pub mod examples;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod input;
+pub mod math;
+pub mod mutate;
+pub mod radix;
+#[cfg(target_family = "wasm")]
+pub mod wasm;
 
+#[cfg(feature = "std")]
 aoc_lib! { year = 2024 }
@@ -0,0 +1,459 @@
+//! Integer number theory and exact linear-system helpers.
+//!
+//! Generalises the one-off pieces from day 13 (2x2 Cramer's rule with an integer check)
+//! and day 14 (modular inverse and a 2-modulus CRT).
+//! Everything is `const fn` so tables and factors can still be computed at compile time.
+//! Intermediate products use `i128`, so any `i64` inputs are safe for the 2-modulus and 2x2 cases.
+
+/// Greatest common divisor, always non-negative.
+#[inline(always)]
+pub const fn gcd(a: i64, b: i64) -> i64 {
+    gcd_i128(a as i128, b as i128) as i64
+}
+
+/// Least common multiple, always non-negative. Returns `None` on overflow.
+pub const fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    let l = (a as i128 / gcd_i128(a as i128, b as i128) * b as i128).abs();
+    if l > i64::MAX as i128 {
+        None
+    } else {
+        Some(l as i64)
+    }
+}
+
+/// Extended Euclid: returns `(g, x, y)` such that `a * x + b * y == g` and `g == gcd(a, b)`.
+pub const fn ext_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (g, x, y) = ext_gcd_i128(a as i128, b as i128);
+    (g as i64, x as i64, y as i64)
+}
+
+/// Inverse of `a` modulo `m`, in `0..m`. `None` if `a` and `m` are not coprime.
+pub const fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    if m <= 0 {
+        return None;
+    }
+    let (g, x, _) = ext_gcd_i128((a as i128).rem_euclid(m as i128), m as i128);
+    if g != 1 {
+        return None;
+    }
+    Some(x.rem_euclid(m as i128) as i64)
+}
+
+/// Combines `x ≡ r1 (mod m1)` and `x ≡ r2 (mod m2)` into `x ≡ r (mod lcm(m1, m2))`.
+/// Moduli need not be coprime. Returns `(r, lcm)` with `r` in `0..lcm`,
+/// or `None` if the congruences contradict each other or `lcm` overflows `i64`.
+pub const fn crt2(r1: i64, m1: i64, r2: i64, m2: i64) -> Option<(i64, i64)> {
+    if m1 <= 0 || m2 <= 0 {
+        return None;
+    }
+    let (m1, m2) = (m1 as i128, m2 as i128);
+    let r1 = (r1 as i128).rem_euclid(m1);
+    let r2 = (r2 as i128).rem_euclid(m2);
+
+    let (g, p, _) = ext_gcd_i128(m1, m2);
+    let diff = r2 - r1;
+    if diff % g != 0 {
+        return None;
+    }
+    let m2_g = m2 / g;
+    let l = m1 * m2_g;
+    if l > i64::MAX as i128 {
+        return None;
+    }
+    // k = diff / g * p (mod m2 / g), then x = r1 + m1 * k
+    let k = ((diff / g) % m2_g * (p % m2_g)).rem_euclid(m2_g);
+    Some(((r1 + m1 * k).rem_euclid(l) as i64, l as i64))
+}
+
+/// Folds [`crt2`] over `(residue, modulus)` pairs.
+/// An empty slice yields `(0, 1)`, which every integer satisfies.
+pub const fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut acc = (0, 1);
+    let mut i = 0;
+    while i < congruences.len() {
+        let (r, m) = congruences[i];
+        acc = match crt2(acc.0, acc.1, r, m) {
+            Some(next) => next,
+            None => return None,
+        };
+        i += 1;
+    }
+    Some(acc)
+}
+
+/// Exact fraction kept in lowest terms with a positive denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+
+    /// Panics if `den == 0`.
+    pub const fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "zero denominator");
+        if num == 0 {
+            return Rational::ZERO;
+        }
+        let g = gcd_i128(num, den);
+        let sign = if den < 0 { -1 } else { 1 };
+        Rational {
+            num: sign * num / g,
+            den: sign * den / g,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn from_int(n: i128) -> Self {
+        Rational { num: n, den: 1 }
+    }
+
+    #[inline(always)]
+    pub const fn num(self) -> i128 {
+        self.num
+    }
+
+    #[inline(always)]
+    pub const fn den(self) -> i128 {
+        self.den
+    }
+
+    /// The value as an integer, if it is one.
+    #[inline(always)]
+    pub const fn to_integer(self) -> Option<i128> {
+        if self.den == 1 {
+            Some(self.num)
+        } else {
+            None
+        }
+    }
+
+    pub const fn add(self, rhs: Self) -> Self {
+        Rational::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+
+    pub const fn sub(self, rhs: Self) -> Self {
+        Rational::new(self.num * rhs.den - rhs.num * self.den, self.den * rhs.den)
+    }
+
+    pub const fn mul(self, rhs: Self) -> Self {
+        Rational::new(self.num * rhs.num, self.den * rhs.den)
+    }
+
+    /// Panics if `rhs` is zero.
+    pub const fn div(self, rhs: Self) -> Self {
+        Rational::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+/// Solves `a * [x0, x1] = b` by Cramer's rule.
+/// Returns `None` if the system has no unique solution (determinant is zero).
+pub const fn solve_2x2(a: [[i64; 2]; 2], b: [i64; 2]) -> Option<[Rational; 2]> {
+    let [[a00, a01], [a10, a11]] = a;
+    let (a00, a01, a10, a11) = (a00 as i128, a01 as i128, a10 as i128, a11 as i128);
+    let (b0, b1) = (b[0] as i128, b[1] as i128);
+
+    let det = a00 * a11 - a01 * a10;
+    if det == 0 {
+        return None;
+    }
+    Some([
+        Rational::new(b0 * a11 - b1 * a01, det),
+        Rational::new(a00 * b1 - a10 * b0, det),
+    ])
+}
+
+/// Like [`solve_2x2`], but only accepts solutions where both unknowns are integers.
+/// This is the day 13 check: a claw machine is winnable only for whole button presses.
+/// Returns `None` if an unknown doesn't fit `i64`.
+pub const fn solve_2x2_integer(a: [[i64; 2]; 2], b: [i64; 2]) -> Option<[i64; 2]> {
+    let [[a00, a01], [a10, a11]] = a;
+    let (a00, a01, a10, a11) = (a00 as i128, a01 as i128, a10 as i128, a11 as i128);
+    let (b0, b1) = (b[0] as i128, b[1] as i128);
+
+    let det = a00 * a11 - a01 * a10;
+    if det == 0 {
+        return None;
+    }
+    let x0 = b0 * a11 - b1 * a01;
+    let x1 = a00 * b1 - a10 * b0;
+    if x0 % det != 0 || x1 % det != 0 {
+        return None;
+    }
+    let (x0, x1) = (x0 / det, x1 / det);
+    let (min, max) = (i64::MIN as i128, i64::MAX as i128);
+    if x0 < min || x0 > max || x1 < min || x1 > max {
+        return None;
+    }
+    Some([x0 as i64, x1 as i64])
+}
+
+/// Solves `a * x = b` for an `N`x`N` system with fraction-free Gaussian elimination (Bareiss).
+/// Every intermediate value is a minor of the augmented matrix, so the entries stay exact.
+/// Returns `None` if the matrix is singular.
+/// Overflow is possible for large `N` with large entries, as minors grow with `N`.
+pub const fn solve<const N: usize>(a: [[i64; N]; N], b: [i64; N]) -> Option<[Rational; N]> {
+    // Work on a copy widened to i128; the right-hand side is eliminated alongside
+    let mut m = [[0i128; N]; N];
+    let mut rhs = [0i128; N];
+    let mut i = 0;
+    while i < N {
+        let mut j = 0;
+        while j < N {
+            m[i][j] = a[i][j] as i128;
+            j += 1;
+        }
+        rhs[i] = b[i] as i128;
+        i += 1;
+    }
+
+    let mut prev = 1i128;
+    let mut k = 0;
+    while k < N {
+        // Partial pivot: any non-zero entry keeps the division exact
+        let mut p = k;
+        while p < N && m[p][k] == 0 {
+            p += 1;
+        }
+        if p == N {
+            return None;
+        }
+        if p != k {
+            let tmp = m[p];
+            m[p] = m[k];
+            m[k] = tmp;
+            let tmp = rhs[p];
+            rhs[p] = rhs[k];
+            rhs[k] = tmp;
+        }
+
+        let pivot = m[k][k];
+        let mut i = k + 1;
+        while i < N {
+            let factor = m[i][k];
+            let mut j = k + 1;
+            while j < N {
+                m[i][j] = (m[i][j] * pivot - factor * m[k][j]) / prev;
+                j += 1;
+            }
+            rhs[i] = (rhs[i] * pivot - factor * rhs[k]) / prev;
+            m[i][k] = 0;
+            i += 1;
+        }
+        prev = pivot;
+        k += 1;
+    }
+
+    // Back substitution on the upper-triangular system
+    let mut x = [Rational::ZERO; N];
+    let mut i = N;
+    while i > 0 {
+        i -= 1;
+        let mut acc = Rational::from_int(rhs[i]);
+        let mut j = i + 1;
+        while j < N {
+            acc = acc.sub(Rational::from_int(m[i][j]).mul(x[j]));
+            j += 1;
+        }
+        x[i] = acc.div(Rational::from_int(m[i][i]));
+    }
+    Some(x)
+}
+
+/// Like [`solve`], but only accepts solutions where every unknown is an integer fitting `i64`.
+pub const fn solve_integer<const N: usize>(a: [[i64; N]; N], b: [i64; N]) -> Option<[i64; N]> {
+    let x = match solve(a, b) {
+        Some(x) => x,
+        None => return None,
+    };
+    let mut out = [0i64; N];
+    let mut i = 0;
+    while i < N {
+        match x[i].to_integer() {
+            Some(v) if v >= i64::MIN as i128 && v <= i64::MAX as i128 => out[i] = v as i64,
+            _ => return None,
+        }
+        i += 1;
+    }
+    Some(out)
+}
+
+const fn gcd_i128(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+const fn ext_gcd_i128(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+        (old_t, t) = (t, old_t - quotient * t);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(i64::MAX, i64::MAX - 1), None);
+    }
+
+    #[test]
+    fn test_ext_gcd_random() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..1000 {
+            let a: i64 = rng.gen_range(-1_000_000_000..1_000_000_000);
+            let b: i64 = rng.gen_range(-1_000_000_000..1_000_000_000);
+            let (g, x, y) = ext_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a as i128 * x as i128 + b as i128 * y as i128, g as i128);
+        }
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        // Same factors as day 14
+        const X_INV: Option<i64> = mod_inverse(103, 101);
+        const Y_INV: Option<i64> = mod_inverse(101, 103);
+        assert_eq!(X_INV.map(|i| i * 103 % 101), Some(1));
+        assert_eq!(Y_INV.map(|i| i * 101 % 103), Some(1));
+        assert_eq!(mod_inverse(-3, 7), Some(2));
+        assert_eq!(mod_inverse(4, 6), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        // Day 14 alignment: t ≡ tx (mod 101), t ≡ ty (mod 103)
+        assert_eq!(crt2(8168 % 101, 101, 8168 % 103, 103), Some((8168, 10403)));
+        // Non-coprime, consistent
+        assert_eq!(crt2(2, 6, 8, 10), Some((8, 30)));
+        // Non-coprime, inconsistent
+        assert_eq!(crt2(1, 6, 2, 10), None);
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[]), Some((0, 1)));
+        const C: Option<(i64, i64)> = crt(&[(1, 4), (3, 6)]);
+        assert_eq!(C, Some((9, 12)));
+    }
+
+    #[test]
+    fn test_crt_random() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..1000 {
+            let x: i64 = rng.gen_range(0..1_000_000_000_000);
+            let moduli: Vec<i64> = (0..4).map(|_| rng.gen_range(1..1000)).collect();
+            let congruences: Vec<(i64, i64)> = moduli.iter().map(|&m| (x % m, m)).collect();
+            let (r, l) = crt(&congruences).unwrap();
+            assert_eq!(x % l, r);
+        }
+    }
+
+    #[test]
+    fn test_solve_2x2_day13() {
+        // Button A: X+94, Y+34 / Button B: X+22, Y+67 / Prize: X=8400, Y=5400
+        assert_eq!(
+            solve_2x2_integer([[94, 22], [34, 67]], [8400, 5400]),
+            Some([80, 40])
+        );
+        // Button A: X+26, Y+66 / Button B: X+67, Y+21 / Prize: X=12748, Y=12176
+        assert_eq!(
+            solve_2x2_integer([[26, 67], [66, 21]], [12748, 12176]),
+            None
+        );
+        let big = 10000000000000;
+        assert_eq!(
+            solve_2x2_integer([[26, 67], [66, 21]], [12748 + big, 12176 + big]),
+            Some([118679050709, 103199174542])
+        );
+        // Quotients past i64 are rejected rather than truncated
+        assert_eq!(
+            solve_2x2_integer([[1, 1], [1, 2]], [i64::MAX, i64::MIN]),
+            None
+        );
+        assert_eq!(
+            solve_2x2_integer([[1, 0], [0, 1]], [i64::MAX, i64::MIN]),
+            Some([i64::MAX, i64::MIN])
+        );
+        assert_eq!(
+            solve_2x2([[1, 2], [2, 4]], [3, 6]),
+            None,
+            "singular system has no unique solution"
+        );
+        assert_eq!(
+            solve_2x2([[2, 0], [0, 4]], [1, 1]),
+            Some([Rational::new(1, 2), Rational::new(1, 4)])
+        );
+    }
+
+    #[test]
+    fn test_solve_n() {
+        const X: Option<[i64; 3]> =
+            solve_integer([[2, 1, -1], [-3, -1, 2], [-2, 1, 2]], [8, -11, -3]);
+        assert_eq!(X, Some([2, 3, -1]));
+        // Zero leading pivot forces a row swap
+        assert_eq!(solve_integer([[0, 1], [1, 0]], [5, 7]), Some([7, 5]));
+        assert_eq!(
+            solve::<3>([[1, 2, 3], [2, 4, 6], [1, 0, 1]], [1, 2, 3]),
+            None
+        );
+        assert_eq!(
+            solve([[3, 0], [0, 1]], [1, 1]),
+            Some([Rational::new(1, 3), Rational::from_int(1)])
+        );
+        assert_eq!(solve_integer([[3, 0], [0, 1]], [1, 1]), None);
+    }
+
+    #[test]
+    fn test_solve_n_matches_2x2() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..1000 {
+            let a = [
+                [rng.gen_range(-100..100), rng.gen_range(-100..100)],
+                [rng.gen_range(-100..100), rng.gen_range(-100..100)],
+            ];
+            let b = [rng.gen_range(-10000..10000), rng.gen_range(-10000..10000)];
+            assert_eq!(solve(a, b), solve_2x2(a, b));
+            assert_eq!(solve_integer(a, b), solve_2x2_integer(a, b));
+        }
+    }
+
+    #[test]
+    fn test_rational() {
+        let a = Rational::new(6, -4);
+        assert_eq!((a.num(), a.den()), (-3, 2));
+        assert_eq!(a.add(Rational::new(1, 2)), Rational::from_int(-1));
+        assert_eq!(a.mul(Rational::new(2, 3)), Rational::from_int(-1));
+        assert_eq!(a.div(Rational::new(3, 4)), Rational::from_int(-2));
+        assert_eq!(a.sub(a), Rational::ZERO);
+        assert_eq!(Rational::new(4, 2).to_integer(), Some(2));
+        assert_eq!(a.to_integer(), None);
+    }
+}
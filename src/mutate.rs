@@ -0,0 +1,472 @@
+//! Input mutators for producing `dayN-alt*.txt` inputs from a real one.
+//!
+//! Each mutation keeps the puzzle's invariants (input sizes, name formats, what the
+//! solvers key on) and records how the answers change: unchanged, renamed through the
+//! same relabeling as the input, or recomputed with a reference solver.
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Derived {
+    Unchanged,
+    /// Comma separated names in the answer go through the same map as the input, then get re-sorted.
+    Renamed(HashMap<String, String>),
+    Recomputed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mutation {
+    pub input: String,
+    answers: [Derived; 2],
+}
+
+impl Mutation {
+    /// Expected answer for `part` of the mutated input, given the answer for the original.
+    pub fn expected(&self, part: u32, original: &str) -> String {
+        match &self.answers[part as usize - 1] {
+            Derived::Unchanged => original.to_string(),
+            Derived::Renamed(map) => {
+                let mut names: Vec<&str> = original
+                    .split(',')
+                    .map(|name| map.get(name).map_or(name, String::as_str))
+                    .collect();
+                names.sort_unstable();
+                names.join(",")
+            }
+            Derived::Recomputed(answer) => answer.clone(),
+        }
+    }
+
+    /// Whether the answer for `part` is known without the original one.
+    pub fn is_recomputed(&self, part: u32) -> bool {
+        matches!(self.answers[part as usize - 1], Derived::Recomputed(_))
+    }
+}
+
+/// Rewrites `input` for `day`, or `None` if there is no mutator for that day.
+/// The same seed always produces the same mutation.
+pub fn mutate(day: u32, input: &str, seed: u64) -> Option<Mutation> {
+    let mut rng = Rng(seed);
+    match day {
+        5 => Some(shuffle_rules(input, &mut rng)),
+        6 => Some(rotate_grid(input, &mut rng)),
+        14 => Some(permute_robots(input, &mut rng)),
+        23 => Some(relabel_computers(input, &mut rng)),
+        24 => Some(rename_wires(input, &mut rng)),
+        _ => None,
+    }
+}
+
+/// SplitMix64, good enough for shuffling and keeps the crate free of an RNG dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+fn join_lines(lines: &[&str]) -> String {
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Day 5: rule order doesn't matter, updates stay as they are.
+fn shuffle_rules(input: &str, rng: &mut Rng) -> Mutation {
+    let (rules, updates) = input.split_once("\n\n").unwrap();
+    let mut rules: Vec<&str> = rules.lines().collect();
+    rng.shuffle(&mut rules);
+    let mut out = join_lines(&rules);
+    out.push('\n');
+    out += &join_lines(&updates.lines().collect::<Vec<_>>());
+    Mutation {
+        input: out,
+        answers: [Derived::Unchanged, Derived::Unchanged],
+    }
+}
+
+/// Day 6: rotates the map by a quarter, half or three-quarter turn. The guard still faces up,
+/// so the route changes and both answers are recomputed.
+fn rotate_grid(input: &str, rng: &mut Rng) -> Mutation {
+    let mut grid: Vec<Vec<u8>> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+    for _ in 0..rng.below(3) + 1 {
+        let (height, width) = (grid.len(), grid[0].len());
+        grid = (0..width)
+            .map(|r| (0..height).map(|c| grid[height - 1 - c][r]).collect())
+            .collect();
+    }
+    let out: String = grid
+        .iter()
+        .flat_map(|row| row.iter().map(|&b| b as char).chain(['\n']))
+        .collect();
+    let (part1, part2) = guard_reference(&grid);
+    Mutation {
+        input: out,
+        answers: [
+            Derived::Recomputed(part1.to_string()),
+            Derived::Recomputed(part2.to_string()),
+        ],
+    }
+}
+
+/// Day 6 by brute force: visited cells, and cells where a new obstacle traps the guard in a loop.
+fn guard_reference(grid: &[Vec<u8>]) -> (usize, usize) {
+    let (height, width) = (grid.len(), grid[0].len());
+    let start = grid
+        .iter()
+        .flatten()
+        .position(|&b| b == b'^')
+        .expect("No guard");
+    let mut seen = vec![0u8; width * height];
+
+    // Walks from the start, returning None on a loop. `seen` holds a direction bitmask per cell.
+    let walk = |extra: Option<usize>, seen: &mut [u8]| -> Option<usize> {
+        seen.fill(0);
+        let (mut r, mut c, mut dir) = ((start / width) as i32, (start % width) as i32, 0);
+        loop {
+            let cell = r as usize * width + c as usize;
+            if seen[cell] & (1 << dir) != 0 {
+                return None;
+            }
+            seen[cell] |= 1 << dir;
+            let (dr, dc) = [(-1, 0), (0, 1), (1, 0), (0, -1)][dir];
+            let (nr, nc) = (r + dr, c + dc);
+            if nr < 0 || nc < 0 || nr >= height as i32 || nc >= width as i32 {
+                return Some(seen.iter().filter(|&&s| s != 0).count());
+            }
+            let next = nr as usize * width + nc as usize;
+            if grid[nr as usize][nc as usize] == b'#' || extra == Some(next) {
+                dir = (dir + 1) % 4;
+            } else {
+                (r, c) = (nr, nc);
+            }
+        }
+    };
+
+    let part1 = walk(None, &mut seen).expect("Guard loops without extra obstacles");
+    let route: Vec<usize> = (0..seen.len())
+        .filter(|&cell| seen[cell] != 0 && cell != start)
+        .collect();
+    let part2 = route
+        .into_iter()
+        .filter(|&cell| walk(Some(cell), &mut seen).is_none())
+        .count();
+    (part1, part2)
+}
+
+/// Day 14: robots are independent, so their order doesn't matter.
+fn permute_robots(input: &str, rng: &mut Rng) -> Mutation {
+    let mut robots: Vec<&str> = input.lines().collect();
+    rng.shuffle(&mut robots);
+    Mutation {
+        input: join_lines(&robots),
+        answers: [Derived::Unchanged, Derived::Unchanged],
+    }
+}
+
+/// Maps every name to a distinct one of the same class, drawn from `pool(class)`.
+fn relabel<'a, C: Ord + Copy>(
+    names: impl Iterator<Item = &'a str>,
+    class: impl Fn(&str) -> C,
+    pool: impl Fn(C) -> Vec<String>,
+    rng: &mut Rng,
+) -> HashMap<String, String> {
+    let mut names: Vec<&str> = names.collect();
+    names.sort_unstable();
+    names.dedup();
+    let mut by_class: alloc::collections::BTreeMap<C, Vec<&str>> = Default::default();
+    for name in names {
+        by_class.entry(class(name)).or_default().push(name);
+    }
+
+    let mut map = HashMap::default();
+    for (c, names) in by_class {
+        let mut targets = pool(c);
+        rng.shuffle(&mut targets);
+        for (name, target) in names.into_iter().zip(targets) {
+            map.insert(name.to_string(), target);
+        }
+    }
+    map
+}
+
+/// Day 23: renames computers, keeping the `t` prefix on exactly the names that had it.
+/// Connections are shuffled and flipped too. Part 2's password is the relabeled clique.
+fn relabel_computers(input: &str, rng: &mut Rng) -> Mutation {
+    let map = relabel(
+        input.lines().flat_map(|line| line.split('-')),
+        |name| name.starts_with('t'),
+        |is_t| {
+            (b'a'..=b'z')
+                .flat_map(|a| (b'a'..=b'z').map(move |b| [a, b]))
+                .filter(|&[a, _]| (a == b't') == is_t)
+                .map(|name| String::from_utf8(name.to_vec()).unwrap())
+                .collect()
+        },
+        rng,
+    );
+
+    let mut links: Vec<String> = input
+        .lines()
+        .map(|line| {
+            let (a, b) = line.split_once('-').unwrap();
+            let (a, b) = (&map[a], &map[b]);
+            if rng.below(2) == 0 {
+                [a.as_str(), "-", b].concat()
+            } else {
+                [b.as_str(), "-", a].concat()
+            }
+        })
+        .collect();
+    rng.shuffle(&mut links);
+    Mutation {
+        input: join_lines(&links.iter().map(String::as_str).collect::<Vec<_>>()),
+        answers: [Derived::Unchanged, Derived::Renamed(map)],
+    }
+}
+
+/// Day 24: renames internal wires, keeping `x`, `y` and `z` wires since their numbering carries
+/// the adder's bits. Gates are shuffled and their operands swapped. Part 2's swapped outputs
+/// go through the same renaming.
+fn rename_wires(input: &str, rng: &mut Rng) -> Mutation {
+    let (initial, gates) = input.split_once("\n\n").unwrap();
+    let is_internal = |name: &str| !name.starts_with(['x', 'y', 'z']);
+    let map = relabel(
+        gates
+            .lines()
+            .flat_map(|line| line.split_whitespace())
+            .filter(|token| {
+                token
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+            })
+            .filter(|name| is_internal(name)),
+        |_| (),
+        |_| {
+            let letters = b'a'..=b'w';
+            letters
+                .flat_map(|a| {
+                    (b'a'..=b'z').flat_map(move |b| (b'a'..=b'z').map(move |c| [a, b, c]))
+                })
+                .map(|name| String::from_utf8(name.to_vec()).unwrap())
+                .collect()
+        },
+        rng,
+    );
+    let rename = |name: &str| map.get(name).map_or(name.to_string(), String::clone);
+
+    let mut gates: Vec<String> = gates
+        .lines()
+        .map(|line| {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let (mut a, mut b) = (rename(tokens[0]), rename(tokens[2]));
+            if rng.below(2) == 0 {
+                core::mem::swap(&mut a, &mut b);
+            }
+            [
+                a.as_str(),
+                " ",
+                tokens[1],
+                " ",
+                &b,
+                " -> ",
+                &rename(tokens[4]),
+            ]
+            .concat()
+        })
+        .collect();
+    rng.shuffle(&mut gates);
+
+    let mut out = join_lines(&initial.lines().collect::<Vec<_>>());
+    out.push('\n');
+    out += &join_lines(&gates.iter().map(String::as_str).collect::<Vec<_>>());
+    Mutation {
+        input: out,
+        answers: [Derived::Unchanged, Derived::Renamed(map)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    const DAY5: &str = "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+";
+
+    const DAY6: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    const DAY23: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn
+";
+
+    fn sorted_lines(input: &str) -> Vec<&str> {
+        let mut lines: Vec<&str> = input.lines().collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    #[test]
+    fn test_day5_answers_unchanged() {
+        for seed in 0..10 {
+            let mutation = mutate(5, DAY5, seed).unwrap();
+            assert_eq!(sorted_lines(&mutation.input), sorted_lines(DAY5));
+            assert_eq!(day5::part1(&mutation.input), 143);
+            assert_eq!(day5::part2(&mutation.input), 123);
+            assert_eq!(mutation.expected(1, "143"), "143");
+        }
+    }
+
+    #[test]
+    fn test_day6_recomputed() {
+        assert_eq!(
+            guard_reference(
+                &DAY6
+                    .lines()
+                    .map(|l| l.as_bytes().to_vec())
+                    .collect::<Vec<_>>()
+            ),
+            (41, 6)
+        );
+        for seed in 0..10 {
+            let mutation = mutate(6, DAY6, seed).unwrap();
+            assert!(mutation.is_recomputed(1) && mutation.is_recomputed(2));
+            assert_eq!(
+                mutation.expected(1, ""),
+                day6::part1_naive(&mutation.input).to_string()
+            );
+            assert_eq!(
+                mutation.expected(2, ""),
+                day6::part2(&mutation.input).to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_day14_permutation() {
+        let input = "p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=-1,2\np=2,0 v=2,-1\n";
+        let mutation = mutate(14, input, 1).unwrap();
+        assert_eq!(sorted_lines(&mutation.input), sorted_lines(input));
+        assert_eq!(mutation.expected(2, "7603"), "7603");
+    }
+
+    #[test]
+    fn test_day23_relabel() {
+        for seed in 0..10 {
+            let mutation = mutate(23, DAY23, seed).unwrap();
+            assert_ne!(mutation.input, DAY23);
+            assert_eq!(day23::part1(&mutation.input), 7);
+            assert_eq!(
+                mutation.expected(2, "co,de,ka,ta"),
+                day23::part2(&mutation.input).as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn test_day24_rename() {
+        let input = "x00: 1\nx01: 0\ny00: 1\ny01: 1\n\nx00 XOR y00 -> z00\nx00 AND y00 -> abc\nx01 XOR y01 -> def\nabc XOR def -> z01\nx01 AND y01 -> ghi\nabc AND def -> jkl\nghi OR jkl -> z02\n";
+        for seed in 0..10 {
+            let mutation = mutate(24, input, seed).unwrap();
+            assert!(!mutation.input.contains("abc"));
+            assert_eq!(day24::part1(&mutation.input), day24::part1(input));
+            assert_eq!(
+                mutation.expected(2, &day24::part2(input)),
+                day24::part2(&mutation.input)
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_day() {
+        assert_eq!(mutate(1, "3   4\n", 0), None);
+    }
+}
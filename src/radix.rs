@@ -0,0 +1,387 @@
+//! LSD radix sort for integer keys, generalized from day 1.
+//!
+//! Keys are sorted relative to the smallest key, and only as many bits as the range
+//! `max - min` needs are sorted, in passes of at most `MAX_DIGIT_BITS`. Passes where every
+//! key has the same digit are skipped. The sort is stable, so pairs keep their input order
+//! among equal keys.
+//!
+//! With the `parallel` feature, `par_sort` and `par_sort_pairs` histogram and scatter
+//! chunks of the input on the rayon pool.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Widest digit per pass, keeps the counts within L1.
+const MAX_DIGIT_BITS: u32 = 11;
+
+/// An unsigned integer key.
+pub trait RadixKey: Copy + Ord {
+    fn to_u64(self) -> u64;
+}
+
+impl RadixKey for u32 {
+    #[inline(always)]
+    fn to_u64(self) -> u64 {
+        self as u64
+    }
+}
+
+impl RadixKey for u64 {
+    #[inline(always)]
+    fn to_u64(self) -> u64 {
+        self
+    }
+}
+
+/// Sorts `keys` in ascending order.
+pub fn sort<K: RadixKey>(keys: &mut [K]) {
+    // SAFETY: the key is the item itself
+    unsafe { sort_by_trusted_key(keys, |&k| k) };
+}
+
+/// Sorts `(key, payload)` pairs by key, keeping the input order of equal keys.
+pub fn sort_pairs<K: RadixKey, V: Copy>(pairs: &mut [(K, V)]) {
+    // SAFETY: the key is a field of the item
+    unsafe { sort_by_trusted_key(pairs, |&(k, _)| k) };
+}
+
+/// Stable sort of `items` by an integer key.
+///
+/// `key` is called once per item, so it may be impure. The items are sorted as `(key, item)`
+/// pairs in a buffer, so prefer [`sort`] or [`sort_pairs`] where they fit.
+pub fn sort_by_key<T: Copy, K: RadixKey, F: Fn(&T) -> K>(items: &mut [T], key: F) {
+    let mut keyed: Vec<(u64, T)> = items
+        .iter()
+        .map(|item| (key(item).to_u64(), *item))
+        .collect();
+    sort_pairs(&mut keyed);
+    items
+        .iter_mut()
+        .zip(keyed)
+        .for_each(|(item, (_, sorted))| *item = sorted);
+}
+
+/// Stable sort of `items` by an integer key, calling `key` again on every pass.
+///
+/// # Safety
+///
+/// `key` must return the same key for an item on every call, the scatter passes write to
+/// bucket offsets from the histograms without bounds checks.
+unsafe fn sort_by_trusted_key<T: Copy, K: RadixKey, F: Fn(&T) -> K>(items: &mut [T], key: F) {
+    let Some(plan) = Plan::new(items, &key) else {
+        return;
+    };
+    let buckets = 1 << plan.width;
+
+    // Histograms for every pass in one sweep, digits don't change between passes
+    let mut counts = vec![0usize; buckets * plan.passes as usize];
+    for item in items.iter() {
+        let k = key(item);
+        for pass in 0..plan.passes as usize {
+            unsafe { *counts.get_unchecked_mut(pass * buckets + plan.digit(k, pass as u32)) += 1 };
+        }
+    }
+
+    let mut buffer = items.to_vec();
+    let (mut src, mut dst) = (&mut *items, &mut buffer[..]);
+    let mut swapped = false;
+    for (pass, counts) in counts.chunks_exact_mut(buckets).enumerate() {
+        // Every key has the same digit, the pass would be a copy
+        if counts.contains(&src.len()) {
+            continue;
+        }
+
+        let mut cumulative = 0;
+        for count in counts.iter_mut() {
+            let temp = *count;
+            *count = cumulative;
+            cumulative += temp;
+        }
+
+        for item in src.iter() {
+            unsafe {
+                let count = counts.get_unchecked_mut(plan.digit(key(item), pass as u32));
+                *dst.get_unchecked_mut(*count) = *item;
+                *count += 1;
+            }
+        }
+        core::mem::swap(&mut src, &mut dst);
+        swapped = !swapped;
+    }
+
+    if swapped {
+        items.copy_from_slice(&buffer);
+    }
+}
+
+/// Digit layout for one sort: keys minus `min`, in `passes` digits of `width` bits.
+struct Plan {
+    min: u64,
+    passes: u32,
+    width: u32,
+}
+
+impl Plan {
+    fn new<T, K: RadixKey>(items: &[T], key: impl Fn(&T) -> K) -> Option<Self> {
+        if items.len() < 2 {
+            return None;
+        }
+        let (min, max) = items.iter().fold((u64::MAX, 0), |(min, max), item| {
+            let k = key(item).to_u64();
+            (min.min(k), max.max(k))
+        });
+        let bits = u64::BITS - (max - min).leading_zeros();
+        if bits == 0 {
+            return None;
+        }
+        let passes = bits.div_ceil(MAX_DIGIT_BITS);
+        Some(Self {
+            min,
+            passes,
+            width: bits.div_ceil(passes),
+        })
+    }
+
+    #[inline(always)]
+    fn digit<K: RadixKey>(&self, key: K, pass: u32) -> usize {
+        (((key.to_u64() - self.min) >> (pass * self.width)) & ((1 << self.width) - 1)) as usize
+    }
+}
+
+#[cfg(feature = "parallel")]
+pub use parallel::{par_sort, par_sort_by_key, par_sort_pairs};
+
+#[cfg(feature = "parallel")]
+mod parallel {
+    use super::*;
+    use rayon::prelude::*;
+
+    /// Below this many items per thread the sequential sort is faster.
+    const MIN_CHUNK: usize = 1 << 14;
+
+    /// Raw destination pointer shared by the scatter tasks, which write disjoint indices.
+    #[derive(Clone, Copy)]
+    struct Dst<T>(*mut T);
+    unsafe impl<T: Send> Send for Dst<T> {}
+    unsafe impl<T: Send> Sync for Dst<T> {}
+
+    impl<T> Dst<T> {
+        // A method, so closures capture the whole `Dst` rather than the raw pointer field
+        fn ptr(self) -> *mut T {
+            self.0
+        }
+    }
+
+    /// Parallel [`sort`](super::sort).
+    pub fn par_sort<K: RadixKey + Send + Sync>(keys: &mut [K]) {
+        // SAFETY: the key is the item itself
+        unsafe { par_sort_by_trusted_key(keys, |&k| k) };
+    }
+
+    /// Parallel [`sort_pairs`](super::sort_pairs).
+    pub fn par_sort_pairs<K: RadixKey + Send + Sync, V: Copy + Send + Sync>(pairs: &mut [(K, V)]) {
+        // SAFETY: the key is a field of the item
+        unsafe { par_sort_by_trusted_key(pairs, |&(k, _)| k) };
+    }
+
+    /// Parallel [`sort_by_key`](super::sort_by_key), also stable and calling `key` once per item.
+    pub fn par_sort_by_key<T, K, F>(items: &mut [T], key: F)
+    where
+        T: Copy + Send + Sync,
+        K: RadixKey,
+        F: Fn(&T) -> K + Sync,
+    {
+        let mut keyed: Vec<(u64, T)> = items
+            .par_iter()
+            .map(|item| (key(item).to_u64(), *item))
+            .collect();
+        par_sort_pairs(&mut keyed);
+        items
+            .par_iter_mut()
+            .zip(keyed)
+            .for_each(|(item, (_, sorted))| *item = sorted);
+    }
+
+    /// Parallel [`sort_by_trusted_key`](super::sort_by_trusted_key).
+    ///
+    /// # Safety
+    ///
+    /// `key` must return the same key for an item on every call, otherwise chunks write out of
+    /// bounds or to the same slots.
+    unsafe fn par_sort_by_trusted_key<T, K, F>(items: &mut [T], key: F)
+    where
+        T: Copy + Send + Sync,
+        K: RadixKey,
+        F: Fn(&T) -> K + Sync,
+    {
+        let chunk = items
+            .len()
+            .div_ceil(rayon::current_num_threads())
+            .max(MIN_CHUNK);
+        if chunk >= items.len() {
+            return sort_by_trusted_key(items, key);
+        }
+        let Some(plan) = Plan::new(items, &key) else {
+            return;
+        };
+        let buckets = 1 << plan.width;
+        let mut buffer = items.to_vec();
+
+        let (mut src, mut dst) = (&mut *items, &mut buffer[..]);
+        let mut swapped = false;
+        for pass in 0..plan.passes {
+            // One histogram per chunk
+            let mut offsets: Vec<Vec<usize>> = src
+                .par_chunks(chunk)
+                .map(|part| {
+                    let mut counts = vec![0usize; buckets];
+                    for item in part {
+                        counts[plan.digit(key(item), pass)] += 1;
+                    }
+                    counts
+                })
+                .collect();
+            if offsets
+                .iter()
+                .fold(vec![0; buckets], |mut total, counts| {
+                    total.iter_mut().zip(counts).for_each(|(t, c)| *t += c);
+                    total
+                })
+                .contains(&src.len())
+            {
+                continue;
+            }
+
+            // Each chunk writes its part of a bucket after the earlier chunks' parts
+            let mut cumulative = 0;
+            for digit in 0..buckets {
+                for counts in offsets.iter_mut() {
+                    let temp = counts[digit];
+                    counts[digit] = cumulative;
+                    cumulative += temp;
+                }
+            }
+
+            let out = Dst(dst.as_mut_ptr());
+            src.par_chunks(chunk)
+                .zip(offsets.par_iter_mut())
+                .for_each(|(part, offsets)| {
+                    for item in part {
+                        let digit = plan.digit(key(item), pass);
+                        // SAFETY: offsets partition 0..len between chunks and digits, as the
+                        // keys are the same as in the histograms
+                        unsafe { *out.ptr().add(offsets[digit]) = *item };
+                        offsets[digit] += 1;
+                    }
+                });
+            core::mem::swap(&mut src, &mut dst);
+            swapped = !swapped;
+        }
+
+        if swapped {
+            items.par_iter_mut().zip(&buffer).for_each(|(i, b)| *i = *b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_sort_u32() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for (len, max) in [
+            (0, 0),
+            (1, 5),
+            (1000, 0),
+            (1000, 1),
+            (1000, 99_999),
+            (5000, u32::MAX),
+        ] {
+            let mut data: Vec<u32> = (0..len).map(|_| rng.gen_range(0..=max)).collect();
+            let mut expected = data.clone();
+            sort(&mut data);
+            expected.sort_unstable();
+            assert_eq!(data, expected, "{len} keys up to {max}");
+        }
+    }
+
+    #[test]
+    fn test_sort_u64() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for (min, max) in [
+            (0, u64::MAX),
+            (u64::MAX - 1000, u64::MAX),
+            (1 << 40, (1 << 40) + 5),
+        ] {
+            let mut data: Vec<u64> = (0..3000).map(|_| rng.gen_range(min..=max)).collect();
+            let mut expected = data.clone();
+            sort(&mut data);
+            expected.sort_unstable();
+            assert_eq!(data, expected);
+        }
+    }
+
+    #[test]
+    fn test_sort_pairs_is_stable() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut pairs: Vec<(u32, usize)> = (0..5000).map(|i| (rng.gen_range(0..50), i)).collect();
+        let mut expected = pairs.clone();
+        sort_pairs(&mut pairs);
+        expected.sort_by_key(|&(k, _)| k);
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn test_sort_by_key_impure() {
+        use core::cell::Cell;
+
+        // A key that changes on every call can't push the scatter out of bounds
+        let calls = Cell::new(0u32);
+        let key = |&item: &u32| {
+            calls.set(calls.get() + 1);
+            item ^ (calls.get() % 3)
+        };
+        let mut data: Vec<u32> = (0..5000).rev().collect();
+        sort_by_key(&mut data, key);
+        assert_eq!(calls.get(), 5000);
+        let mut expected: Vec<u32> = (0..5000).collect();
+        expected.sort_unstable();
+        data.sort_unstable();
+        assert_eq!(data, expected);
+
+        let mut pairs: Vec<(u32, usize)> = (0..1000).map(|i| (i as u32 % 7, i)).collect();
+        let mut expected = pairs.clone();
+        sort_by_key(&mut pairs, |&(k, _)| k);
+        expected.sort_by_key(|&(k, _)| k);
+        assert_eq!(pairs, expected);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_par_sort() {
+        // Force several chunks even on a single core machine
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        let mut rng = StdRng::seed_from_u64(4);
+        for max in [0, 255, u64::MAX] {
+            let mut data: Vec<u64> = (0..200_000).map(|_| rng.gen_range(0..=max)).collect();
+            let mut expected = data.clone();
+            pool.install(|| par_sort(&mut data));
+            expected.sort_unstable();
+            assert_eq!(data, expected);
+        }
+
+        let mut pairs: Vec<(u32, u32)> =
+            (0..200_000).map(|i| (rng.gen_range(0..1000), i)).collect();
+        let mut expected = pairs.clone();
+        pool.install(|| par_sort_pairs(&mut pairs));
+        expected.sort_by_key(|&(k, _)| k);
+        assert_eq!(pairs, expected);
+    }
+}
@@ -1,9 +1,9 @@
-#[aoc(day25, part1)] // FIXME: change day25 here and elsewhere to current day
+#[cfg_attr(feature = "std", aoc(day25, part1))] // FIXME: change day25 here and elsewhere to current day
 pub fn part1(input: &str) -> u64 {
     0
 }
 
-#[aoc(day25, part2)]
+#[cfg_attr(feature = "std", aoc(day25, part2))]
 pub fn part2(input: &str) -> u64 {
     0
 }
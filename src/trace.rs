@@ -0,0 +1,230 @@
+//! Per-phase timing for solvers, enabled with the `trace` cargo feature.
+//!
+//! `trace_span!("day16::part2")` opens a span that closes at the end of the enclosing scope.
+//! `trace_phase!("search")` closes the previous phase (if any) inside the current span
+//! and opens a new one, so phases can be marked in a straight line of code.
+//! Without the feature both macros expand to nothing, so benchmarks are unaffected.
+//!
+//! Recorded spans are dumped as Chrome trace JSON (`chrome://tracing`, Perfetto).
+
+#[cfg(feature = "trace")]
+#[macro_export]
+macro_rules! trace_span {
+    ($name:expr) => {
+        let _trace_span = $crate::trace::Span::enter($name);
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+macro_rules! trace_span {
+    ($name:expr) => {};
+}
+
+#[cfg(feature = "trace")]
+#[macro_export]
+macro_rules! trace_phase {
+    ($name:expr) => {
+        $crate::trace::phase($name);
+    };
+}
+
+#[cfg(not(feature = "trace"))]
+#[macro_export]
+macro_rules! trace_phase {
+    ($name:expr) => {};
+}
+
+#[cfg(feature = "trace")]
+pub use recorder::*;
+
+#[cfg(feature = "trace")]
+mod recorder {
+    use std::cell::{Cell, RefCell};
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+    use std::path::Path;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Instant;
+
+    /// A closed span. Times are in nanoseconds since the first recorded span.
+    #[derive(Clone, Debug)]
+    pub struct Event {
+        pub name: &'static str,
+        pub thread: u64,
+        pub depth: usize,
+        pub start_ns: u64,
+        pub duration_ns: u64,
+    }
+
+    struct Open {
+        name: &'static str,
+        start_ns: u64,
+        is_phase: bool,
+    }
+
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    static EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+    static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+    thread_local! {
+        static OPEN: RefCell<Vec<Open>> = const { RefCell::new(Vec::new()) };
+        static THREAD: Cell<u64> = const { Cell::new(0) };
+    }
+
+    #[inline(always)]
+    fn now_ns() -> u64 {
+        EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+    }
+
+    fn thread_id() -> u64 {
+        THREAD.with(|t| {
+            if t.get() == 0 {
+                t.set(NEXT_THREAD.fetch_add(1, Ordering::Relaxed));
+            }
+            t.get()
+        })
+    }
+
+    fn close_top(open: &mut Vec<Open>, end_ns: u64) {
+        let top = open.pop().unwrap();
+        let event = Event {
+            name: top.name,
+            thread: thread_id(),
+            depth: open.len(),
+            start_ns: top.start_ns,
+            duration_ns: end_ns - top.start_ns,
+        };
+        EVENTS.lock().unwrap().push(event);
+    }
+
+    /// Guard for a span; closes the span and any phases still open inside it on drop.
+    pub struct Span {
+        depth: usize,
+    }
+
+    impl Span {
+        pub fn enter(name: &'static str) -> Self {
+            let start_ns = now_ns();
+            let depth = OPEN.with_borrow_mut(|open| {
+                open.push(Open {
+                    name,
+                    start_ns,
+                    is_phase: false,
+                });
+                open.len()
+            });
+            Span { depth }
+        }
+    }
+
+    impl Drop for Span {
+        fn drop(&mut self) {
+            let end_ns = now_ns();
+            OPEN.with_borrow_mut(|open| {
+                while open.len() >= self.depth {
+                    close_top(open, end_ns);
+                }
+            });
+        }
+    }
+
+    /// Ends the current phase of the innermost span and starts `name`.
+    pub fn phase(name: &'static str) {
+        let now = now_ns();
+        OPEN.with_borrow_mut(|open| {
+            if open.last().is_some_and(|o| o.is_phase) {
+                close_top(open, now);
+            }
+            open.push(Open {
+                name,
+                start_ns: now,
+                is_phase: true,
+            });
+        });
+    }
+
+    /// Removes and returns every span closed so far, across all threads.
+    pub fn take_events() -> Vec<Event> {
+        std::mem::take(&mut *EVENTS.lock().unwrap())
+    }
+
+    /// Writes `events` in the Chrome trace event format ("X" complete events, times in µs).
+    pub fn write_chrome_trace<W: Write>(mut w: W, events: &[Event]) -> io::Result<()> {
+        writeln!(w, "{{\"traceEvents\":[")?;
+        for (i, e) in events.iter().enumerate() {
+            let sep = if i + 1 < events.len() { "," } else { "" };
+            writeln!(
+                w,
+                "{{\"name\":\"{}\",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{:.3},\"dur\":{:.3},\"pid\":1,\"tid\":{}}}{}",
+                e.name,
+                if e.depth == 0 { "solver" } else { "phase" },
+                e.start_ns as f64 / 1000.0,
+                e.duration_ns as f64 / 1000.0,
+                e.thread,
+                sep
+            )?;
+        }
+        writeln!(w, "],\"displayTimeUnit\":\"ns\"}}")
+    }
+
+    /// Takes all recorded spans and writes them to `path` as Chrome trace JSON.
+    pub fn dump_chrome_trace<P: AsRef<Path>>(path: P) -> io::Result<()> {
+        let events = take_events();
+        write_chrome_trace(BufWriter::new(File::create(path)?), &events)
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_nest_inside_span() {
+        take_events();
+        {
+            trace_span!("outer");
+            trace_phase!("parse");
+            trace_phase!("search");
+            {
+                trace_span!("inner");
+            }
+            trace_phase!("reduce");
+        }
+        let events = take_events();
+        let names: Vec<(&str, usize)> = events.iter().map(|e| (e.name, e.depth)).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("parse", 1),
+                ("inner", 2),
+                ("search", 1),
+                ("reduce", 1),
+                ("outer", 0)
+            ]
+        );
+
+        let outer = events.last().unwrap();
+        for e in &events[..events.len() - 1] {
+            assert!(e.start_ns >= outer.start_ns);
+            assert!(e.start_ns + e.duration_ns <= outer.start_ns + outer.duration_ns);
+        }
+
+        let mut json = Vec::new();
+        write_chrome_trace(&mut json, &events).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with("{\"traceEvents\":["));
+        assert_eq!(json.matches("\"ph\":\"X\"").count(), 5);
+    }
+
+    #[test]
+    fn test_solver_spans() {
+        take_events();
+        crate::day25::part1("");
+        let events = take_events();
+        assert!(events
+            .iter()
+            .any(|e| e.name == "day25::part1" && e.depth == 0));
+    }
+}
@@ -0,0 +1,70 @@
+//! WebAssembly entry points.
+//!
+//! Build with
+//! `cargo rustc --release --lib --target wasm32-unknown-unknown --features lean --crate-type cdylib`
+//! and load the module with `wasm/aoc.mjs`, which wraps the raw exports below
+//! into `solve(day, part, input) -> string`.
+//!
+//! Errors use the negative codes of the C ABI in `ffi.rs`, input is validated the same way.
+
+use crate::dispatch;
+use crate::input::{fits_layout, normalize};
+use alloc::alloc::{alloc, dealloc, Layout};
+use alloc::string::String;
+
+pub const AOC_ERR_UNKNOWN_PUZZLE: i32 = -2;
+pub const AOC_ERR_INVALID_INPUT: i32 = -3;
+
+static mut ANSWER: String = String::new();
+
+/// Runs the solver for `day` and `part`, or returns a negative `AOC_ERR_*` code.
+pub fn solve(day: u32, part: u32, input: &[u8]) -> Result<String, i32> {
+    if !(1..=25).contains(&day) || !(1..=2).contains(&part) {
+        return Err(AOC_ERR_UNKNOWN_PUZZLE);
+    }
+    let input = core::str::from_utf8(input).map_err(|_| AOC_ERR_INVALID_INPUT)?;
+    let input = normalize(input);
+    if input.is_empty() || !fits_layout(day, &input) {
+        return Err(AOC_ERR_INVALID_INPUT);
+    }
+    dispatch::solve_normalized(day, part, &input).ok_or(AOC_ERR_UNKNOWN_PUZZLE)
+}
+
+/// Allocates `len` bytes for the host to copy the input into.
+#[no_mangle]
+pub extern "C" fn aoc_alloc(len: usize) -> *mut u8 {
+    unsafe { alloc(Layout::array::<u8>(len.max(1)).unwrap()) }
+}
+
+/// Frees a buffer returned by `aoc_alloc`.
+///
+/// # Safety
+/// `ptr` must come from `aoc_alloc(len)` and not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_free(ptr: *mut u8, len: usize) {
+    dealloc(ptr, Layout::array::<u8>(len.max(1)).unwrap())
+}
+
+/// Solves the puzzle for the UTF-8 input at `ptr` and returns the answer length in bytes,
+/// or a negative `AOC_ERR_*` code. The answer stays readable at `aoc_answer_ptr()` until the
+/// next call.
+///
+/// # Safety
+/// `ptr` must point to `len` readable bytes.
+#[no_mangle]
+#[allow(static_mut_refs)]
+pub unsafe extern "C" fn aoc_solve(day: u32, part: u32, ptr: *const u8, len: usize) -> i32 {
+    match solve(day, part, core::slice::from_raw_parts(ptr, len)) {
+        Ok(answer) => {
+            ANSWER = answer;
+            ANSWER.len() as i32
+        }
+        Err(code) => code,
+    }
+}
+
+#[no_mangle]
+#[allow(static_mut_refs)]
+pub extern "C" fn aoc_answer_ptr() -> *const u8 {
+    unsafe { ANSWER.as_ptr() }
+}
@@ -0,0 +1,22 @@
+// Loads the wasm build of the solvers and exposes `solve(day, part, input) -> string`.
+// `input` is a string or UTF-8 bytes, errors from `aoc_solve` are thrown.
+const ERRORS = { [-2]: "unknown puzzle", [-3]: "invalid input" };
+
+export async function load(bytes) {
+  const { instance } = await WebAssembly.instantiate(bytes, {});
+  const wasm = instance.exports;
+  const encoder = new TextEncoder();
+  const decoder = new TextDecoder();
+
+  function solve(day, part, input) {
+    const bytes = typeof input === "string" ? encoder.encode(input) : input;
+    const ptr = wasm.aoc_alloc(bytes.length);
+    new Uint8Array(wasm.memory.buffer, ptr, bytes.length).set(bytes);
+    const len = wasm.aoc_solve(day, part, ptr, bytes.length);
+    wasm.aoc_free(ptr, bytes.length);
+    if (len < 0) throw new Error(`day ${day} part ${part}: ${ERRORS[len] ?? `error ${len}`}`);
+    return decoder.decode(new Uint8Array(wasm.memory.buffer, wasm.aoc_answer_ptr(), len));
+  }
+
+  return { solve };
+}
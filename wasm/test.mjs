@@ -0,0 +1,41 @@
+// Usage: node wasm/test.mjs <path to .wasm>
+// Checks the wasm build against examples and, for each local input, against a plain JS day 22.
+import { readFileSync, existsSync } from "node:fs";
+import assert from "node:assert/strict";
+import { load } from "./aoc.mjs";
+
+const { solve } = await load(readFileSync(process.argv[2]));
+
+assert.equal(solve(9, 1, "2333133121414131402\n"), "1928");
+assert.equal(solve(9, 2, "2333133121414131402\n"), "2858");
+assert.throws(() => solve(26, 1, "1\n"), /unknown puzzle/);
+assert.throws(() => solve(22, 1, new Uint8Array([0x31, 0xff, 0x0a])), /invalid input/);
+assert.throws(() => solve(22, 1, ""), /invalid input/);
+
+function day22Part1(input) {
+  let sum = 0;
+  for (const line of input.split("\n").filter((l) => l.length > 0)) {
+    let secret = Number(line);
+    for (let i = 0; i < 2000; i++) {
+      secret = (secret ^ (secret << 6)) & 0xffffff;
+      secret = (secret ^ (secret >> 5)) & 0xffffff;
+      secret = (secret ^ (secret << 11)) & 0xffffff;
+    }
+    sum += secret;
+  }
+  return String(sum);
+}
+
+// The example secrets are too short for the input layout
+assert.equal(solve(22, 1, "100000\n1000000\n2024000\n"), day22Part1("100000\n1000000\n2024000\n"));
+
+const randomInput = Array.from({ length: 1500 }, () => 100000 + Math.floor(Math.random() * 16000000)).join("\n") + "\n";
+assert.equal(solve(22, 1, randomInput), day22Part1(randomInput));
+
+const day22 = new URL("../input/2024/day22.txt", import.meta.url);
+if (existsSync(day22)) {
+  const input = readFileSync(day22, "utf8");
+  if (input.length > 0) assert.equal(solve(22, 1, input), day22Part1(input));
+}
+
+console.log("wasm ok");